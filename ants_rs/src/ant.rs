@@ -0,0 +1,362 @@
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rand::Rng;
+
+use crate::ant_store::AntMut;
+use crate::config::{Caste, SimulationConfig};
+use crate::environment::{Environment, Layer, Position};
+use crate::homing::DistanceField;
+
+// Mirrors `models.Ant`.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Clone, Debug)]
+pub struct Ant {
+    pub position: Position,
+    pub previous_position: Option<Position>,
+    pub food: f64,
+    pub returning_to_queen: bool,
+    pub id: u64,
+    // Index of the colony the ant belongs to
+    pub colony: usize,
+    pub caste: Caste,
+    pub carrying_capacity: f64,
+    pub source_has_more_food: bool,
+    pub food_source_position: Option<Position>,
+    // Food source a nestmate recruited this ant to, until it gets there
+    pub recruit_target: Option<Position>,
+    // Only spent while `energy.enabled`; the colony fills it up when the ant
+    // joins
+    pub energy: f64,
+
+    // Lifespan attributes
+    pub age: f64,
+    pub lifespan: f64,
+}
+
+impl Ant {
+    pub fn new(position: Position, id: u64, lifespan: f64, carrying_capacity: f64) -> Ant {
+        Ant {
+            position,
+            previous_position: None,
+            food: 0.0,
+            returning_to_queen: false,
+            id,
+            colony: 0,
+            caste: Caste::Forager,
+            carrying_capacity,
+            source_has_more_food: false,
+            food_source_position: None,
+            recruit_target: None,
+            energy: 0.0,
+            age: 0.0,
+            lifespan,
+        }
+    }
+}
+
+// The movement and foraging rules, applied in place to one ant's entries in
+// its colony's `AntStore`.
+impl AntMut<'_> {
+    // Moves, forages and marks the trail for one step. Food handed to the
+    // queen is added to `food_store`. A recruit on a tandem run follows
+    // `leader`, the position of the nestmate leading it. Ageing and death are
+    // handled by the colony, which sweeps the whole age array before anyone
+    // moves.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update<R: Rng>(
+        &mut self,
+        environment: &mut Environment,
+        queen_position: Position,
+        homing: Option<&DistanceField>,
+        leader: Option<Position>,
+        food_store: &mut f64,
+        config: &SimulationConfig,
+        rng: &mut R,
+    ) {
+        if *self.returning_to_queen {
+            match homing.and_then(|field| field.next_step(*self.position, &environment.terrain)) {
+                Some(next) => {
+                    *self.previous_position = Some(*self.position);
+                    *self.position = next;
+                }
+                // Greedy homing, or stranded where the field can't help
+                None => self.move_towards(queen_position, environment),
+            }
+            if *self.position == queen_position {
+                self.deposit_food(food_store, config);
+            }
+        } else if let Some(target) = *self.recruit_target {
+            let start = *self.position;
+            self.move_towards(leader.unwrap_or(target), environment);
+            self.collect_food(environment);
+            // Arrived, found food on the way, or stuck behind a wall: back to
+            // foraging as usual
+            let stuck = *self.position == start && leader.is_none();
+            if *self.position == target || *self.food > 0.0 || stuck {
+                *self.recruit_target = None;
+            }
+        } else {
+            self.do_move(environment, config, rng);
+            self.collect_food(environment);
+        }
+        self.leave_pheromone(environment);
+    }
+
+    fn do_move<R: Rng>(
+        &mut self,
+        environment: &Environment,
+        config: &SimulationConfig,
+        rng: &mut R,
+    ) {
+        // Check for food within perception radius
+        let radius = config.perception_radius_of(self.caste);
+        match environment.closest_visible_food(*self.position, radius, self.colony) {
+            Some(closest_food) => {
+                *self.previous_position = Some(*self.position);
+                self.move_towards(closest_food, environment);
+            }
+            // Move based on pheromones and randomness
+            None => self.random_move(environment, config, rng),
+        }
+    }
+
+    fn random_move<R: Rng>(
+        &mut self,
+        environment: &Environment,
+        config: &SimulationConfig,
+        rng: &mut R,
+    ) {
+        let boundary = config.boundary_mode;
+        let position = *self.position;
+        let neighbours: Vec<Position> = config
+            .neighborhood
+            .moves()
+            .iter()
+            .filter_map(|&(dx, dy)| boundary.step(position, dx, dy, config.grid_size))
+            .filter(|&pos| environment.terrain.is_open(pos))
+            .collect();
+        if neighbours.is_empty() {
+            // Walled in; wait where we are
+            *self.previous_position = Some(position);
+            return;
+        }
+        let neighbours = self.avoid_rivals(neighbours, environment);
+
+        // Exclude the previous position to avoid backtracking
+        let mut new_positions: Vec<Position> = neighbours
+            .iter()
+            .copied()
+            .filter(|&pos| Some(pos) != *self.previous_position)
+            .collect();
+        if new_positions.is_empty() {
+            // All moves lead back; include previous position to avoid being stuck
+            new_positions = neighbours;
+        }
+
+        // Choose next move based on pheromones and randomness
+        *self.previous_position = Some(position);
+        *self.position =
+            self.choose_move_based_on_pheromones(&new_positions, environment, config, rng);
+    }
+
+    // Under `encounter_mode = "avoid"`, drops the cells rivals stood on at
+    // the start of the step, unless that leaves nowhere to go.
+    fn avoid_rivals(&self, neighbours: Vec<Position>, environment: &Environment) -> Vec<Position> {
+        let Some(occupancy) = &environment.occupancy else {
+            return neighbours;
+        };
+        let clear: Vec<Position> = neighbours
+            .iter()
+            .copied()
+            .filter(|&pos| !occupancy.has_rival(pos, self.colony))
+            .collect();
+        if clear.is_empty() {
+            neighbours
+        } else {
+            clear
+        }
+    }
+
+    fn choose_move_based_on_pheromones<R: Rng>(
+        &self,
+        new_positions: &[Position],
+        environment: &Environment,
+        config: &SimulationConfig,
+        rng: &mut R,
+    ) -> Position {
+        let epsilon = 1e-6;
+        let colony = self.colony;
+        let num_positions = new_positions.len() as f64;
+        let randomness_factor = config.randomness_factor_of(self.caste);
+
+        let pheromone_scores: Vec<f64> = new_positions
+            .iter()
+            .map(|&pos| {
+                let trail_pheromone = environment.pheromone_level(pos, colony, Layer::Trail) as f64;
+                let regular_pheromone =
+                    environment.pheromone_level(pos, colony, Layer::Regular) as f64;
+                if *self.returning_to_queen {
+                    // Follow 'regular' pheromone trails when returning
+                    (regular_pheromone + epsilon) / (1.0 + trail_pheromone)
+                } else {
+                    // Avoid 'regular' pheromones and prefer 'food' and 'rich' pheromones,
+                    // and rally to nestmates' fights
+                    let food_pheromone =
+                        environment.pheromone_level(pos, colony, Layer::FoodPheromone) as f64;
+                    let rich_pheromone =
+                        environment.pheromone_level(pos, colony, Layer::Rich) as f64;
+                    let alarm_pheromone =
+                        environment.pheromone_level(pos, colony, Layer::Alarm) as f64;
+                    (food_pheromone + rich_pheromone + alarm_pheromone + epsilon)
+                        / (1.0 + regular_pheromone + trail_pheromone)
+                }
+            })
+            .collect();
+
+        let total_pheromone: f64 = pheromone_scores.iter().sum();
+        let probabilities: Vec<f64> = pheromone_scores
+            .iter()
+            .map(|score| {
+                let pheromone_prob = if total_pheromone > 0.0 {
+                    (1.0 - randomness_factor) * (score / total_pheromone)
+                } else {
+                    0.0
+                };
+                pheromone_prob + randomness_factor / num_positions
+            })
+            .collect();
+
+        new_positions[weighted_choice(&probabilities, rng)]
+    }
+
+    // One greedy step, diagonal if need be (on the hex grid, whichever
+    // neighbour is closest). When that cell is a wall, the next best step
+    // that still gets closer is tried instead, and failing all of them the
+    // ant waits.
+    pub fn move_towards(&mut self, target_position: Position, environment: &Environment) {
+        let grid_size = environment.config.grid_size;
+        let boundary = environment.config.boundary_mode;
+        let neighborhood = environment.config.neighborhood;
+        let position = *self.position;
+        let (dx, dy) = boundary.delta(position, target_position, grid_size, neighborhood);
+
+        let next = neighborhood
+            .greedy_steps(dx, dy)
+            .into_iter()
+            .find_map(|step| {
+                environment
+                    .terrain
+                    .step(position, step, boundary, neighborhood)
+            });
+
+        *self.previous_position = Some(position);
+        if let Some(next) = next {
+            *self.position = next;
+        }
+    }
+
+    fn collect_food(&mut self, environment: &mut Environment) {
+        let position = *self.position;
+        if !environment.food_visible_to(position, self.colony) {
+            return;
+        }
+        let available_food = environment.food_amount(position) as f64;
+        if available_food > 0.0 {
+            environment.claim_food(position, self.colony);
+            let food_needed = self.carrying_capacity - *self.food;
+            let food_to_collect = food_needed.min(available_food);
+            *self.food += food_to_collect;
+            environment.take_food(position, food_to_collect as f32);
+            *self.food_source_position = Some(position);
+            *self.source_has_more_food = environment.food_amount(position) > 0.0;
+            if *self.food >= self.carrying_capacity {
+                *self.returning_to_queen = true;
+            }
+        }
+    }
+
+    fn deposit_food(&mut self, food_store: &mut f64, config: &SimulationConfig) {
+        let delivered = *self.food;
+        *food_store += delivered;
+        *self.food = 0.0;
+        *self.returning_to_queen = false;
+        *self.source_has_more_food = false;
+        *self.food_source_position = None;
+
+        // Extend lifespan upon contribution; a hungry ant that ate its load
+        // on the way home has none
+        if delivered > 0.0 {
+            *self.lifespan += config.ant.lifespan_extension_on_contribution;
+        }
+    }
+
+    fn leave_pheromone(&self, environment: &mut Environment) {
+        let multiple_pheromones = environment.config.enable_multiple_pheromones;
+        let layer = if *self.returning_to_queen {
+            if *self.source_has_more_food && multiple_pheromones {
+                Layer::Rich
+            } else {
+                Layer::Regular
+            }
+        } else if multiple_pheromones {
+            Layer::FoodPheromone
+        } else {
+            Layer::Regular
+        };
+        environment.deposit(*self.position, self.colony, layer);
+        environment.deposit(*self.position, self.colony, Layer::Trail);
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Ant {
+    #[new]
+    #[pyo3(signature = (position, id, lifespan, carrying_capacity, colony=0, caste=Caste::Forager))]
+    fn py_new(
+        position: Position,
+        id: u64,
+        lifespan: f64,
+        carrying_capacity: f64,
+        colony: usize,
+        caste: Caste,
+    ) -> Ant {
+        Ant {
+            colony,
+            caste,
+            ..Ant::new(position, id, lifespan, carrying_capacity)
+        }
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.id
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Ant(id={}, position={:?}, food={:.1}, returning_to_queen={})",
+            self.id,
+            self.position,
+            self.food,
+            if self.returning_to_queen {
+                "True"
+            } else {
+                "False"
+            }
+        )
+    }
+}
+
+// Same cumulative-weight draw as `random.choices(..., weights=..., k=1)`.
+fn weighted_choice<R: Rng>(weights: &[f64], rng: &mut R) -> usize {
+    let total: f64 = weights.iter().sum();
+    let target = rng.gen::<f64>() * total;
+    let mut cumulative = 0.0;
+    for (i, weight) in weights.iter().enumerate() {
+        cumulative += weight;
+        if target < cumulative {
+            return i;
+        }
+    }
+    weights.len() - 1
+}
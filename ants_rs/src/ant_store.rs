@@ -0,0 +1,261 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ant::Ant;
+use crate::config::Caste;
+use crate::environment::Position;
+
+// Generational id: slot index in the low 24 bits, the colony in the next 8
+// and the slot's generation in the high 32 bits. A slot is reused after its
+// ant dies, but with a bumped generation, so an id never refers to two
+// different ants, and ids from different colonies never collide.
+pub type AntId = u64;
+
+const VACANT: u32 = u32::MAX;
+const SLOT_BITS: u32 = 24;
+const SLOT_MASK: u32 = (1 << SLOT_BITS) - 1;
+
+// Most ants a colony can hold at once; every slot id below this is in use.
+pub const MAX_ANTS: usize = 1 << SLOT_BITS;
+
+// Returned by `AntStore::insert` when the colony already holds `MAX_ANTS`.
+#[derive(Debug)]
+pub struct ColonyFull {
+    pub colony: u8,
+}
+
+impl fmt::Display for ColonyFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "colony {} already holds the maximum of {MAX_ANTS} ants",
+            self.colony
+        )
+    }
+}
+
+impl std::error::Error for ColonyFull {}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct Slot {
+    generation: u32,
+    // Index into the dense arrays, or `VACANT`.
+    dense: u32,
+}
+
+fn make_id(colony: u8, slot: u32, generation: u32) -> AntId {
+    ((generation as u64) << 32) | ((colony as u64) << SLOT_BITS) | slot as u64
+}
+
+// (colony, slot, generation)
+fn split_id(id: AntId) -> (u8, u32, u32) {
+    (
+        (id as u32 >> SLOT_BITS) as u8,
+        id as u32 & SLOT_MASK,
+        (id >> 32) as u32,
+    )
+}
+
+// The colony's ants as parallel arrays. Live ants are packed at the front of
+// every array; removing one swaps the last ant into its place, so storage is
+// never shifted and its capacity is reused by later hatchlings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AntStore {
+    pub colony: u8,
+    pub ids: Vec<AntId>,
+    pub castes: Vec<Caste>,
+    pub positions: Vec<Position>,
+    pub previous_positions: Vec<Option<Position>>,
+    pub food: Vec<f64>,
+    pub returning_to_queen: Vec<bool>,
+    pub carrying_capacity: Vec<f64>,
+    pub source_has_more_food: Vec<bool>,
+    pub food_source_positions: Vec<Option<Position>>,
+    pub recruit_targets: Vec<Option<Position>>,
+    // The nestmate leading each ant on a tandem run, if any
+    pub guides: Vec<Option<AntId>>,
+    pub energies: Vec<f64>,
+    pub ages: Vec<f64>,
+    pub lifespans: Vec<f64>,
+    slots: Vec<Slot>,
+    free_slots: Vec<u32>,
+}
+
+impl AntStore {
+    pub fn with_capacity(colony: u8, capacity: usize) -> AntStore {
+        AntStore {
+            colony,
+            ids: Vec::with_capacity(capacity),
+            castes: Vec::with_capacity(capacity),
+            positions: Vec::with_capacity(capacity),
+            previous_positions: Vec::with_capacity(capacity),
+            food: Vec::with_capacity(capacity),
+            returning_to_queen: Vec::with_capacity(capacity),
+            carrying_capacity: Vec::with_capacity(capacity),
+            source_has_more_food: Vec::with_capacity(capacity),
+            food_source_positions: Vec::with_capacity(capacity),
+            recruit_targets: Vec::with_capacity(capacity),
+            guides: Vec::with_capacity(capacity),
+            energies: Vec::with_capacity(capacity),
+            ages: Vec::with_capacity(capacity),
+            lifespans: Vec::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            free_slots: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    // Adds an ant and returns its freshly assigned id; `ant.id` and
+    // `ant.colony` are ignored. Fails once the colony holds `MAX_ANTS`, as
+    // the next slot would spill into the colony bits of the id.
+    pub fn insert(&mut self, ant: Ant) -> Result<AntId, ColonyFull> {
+        let dense = self.ids.len() as u32;
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                self.slots[slot as usize].dense = dense;
+                slot
+            }
+            None if self.slots.len() < MAX_ANTS => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    dense,
+                });
+                (self.slots.len() - 1) as u32
+            }
+            None => {
+                return Err(ColonyFull {
+                    colony: self.colony,
+                })
+            }
+        };
+        let id = make_id(self.colony, slot, self.slots[slot as usize].generation);
+
+        self.ids.push(id);
+        self.castes.push(ant.caste);
+        self.positions.push(ant.position);
+        self.previous_positions.push(ant.previous_position);
+        self.food.push(ant.food);
+        self.returning_to_queen.push(ant.returning_to_queen);
+        self.carrying_capacity.push(ant.carrying_capacity);
+        self.source_has_more_food.push(ant.source_has_more_food);
+        self.food_source_positions.push(ant.food_source_position);
+        self.recruit_targets.push(ant.recruit_target);
+        self.guides.push(None);
+        self.energies.push(ant.energy);
+        self.ages.push(ant.age);
+        self.lifespans.push(ant.lifespan);
+        Ok(id)
+    }
+
+    // Removes the ant at dense index `index`; the last ant takes its place.
+    pub fn remove(&mut self, index: usize) {
+        let (_, slot, _) = split_id(self.ids[index]);
+        let last = self.ids.len() - 1;
+        if index != last {
+            let (_, moved_slot, _) = split_id(self.ids[last]);
+            self.slots[moved_slot as usize].dense = index as u32;
+        }
+        let freed = &mut self.slots[slot as usize];
+        freed.dense = VACANT;
+        freed.generation = freed.generation.wrapping_add(1);
+        self.free_slots.push(slot);
+
+        self.ids.swap_remove(index);
+        self.castes.swap_remove(index);
+        self.positions.swap_remove(index);
+        self.previous_positions.swap_remove(index);
+        self.food.swap_remove(index);
+        self.returning_to_queen.swap_remove(index);
+        self.carrying_capacity.swap_remove(index);
+        self.source_has_more_food.swap_remove(index);
+        self.food_source_positions.swap_remove(index);
+        self.recruit_targets.swap_remove(index);
+        self.guides.swap_remove(index);
+        self.energies.swap_remove(index);
+        self.ages.swap_remove(index);
+        self.lifespans.swap_remove(index);
+    }
+
+    // Dense index of a live ant, or `None` if that ant has died or belongs
+    // to another colony.
+    pub fn index_of(&self, id: AntId) -> Option<usize> {
+        let (colony, slot, generation) = split_id(id);
+        if colony != self.colony {
+            return None;
+        }
+        let slot = self.slots.get(slot as usize)?;
+        (slot.generation == generation && slot.dense != VACANT).then_some(slot.dense as usize)
+    }
+
+    pub fn contains(&self, id: AntId) -> bool {
+        self.index_of(id).is_some()
+    }
+
+    // Copies the ant at `index` out of the arrays.
+    pub fn get(&self, index: usize) -> Ant {
+        Ant {
+            position: self.positions[index],
+            previous_position: self.previous_positions[index],
+            food: self.food[index],
+            returning_to_queen: self.returning_to_queen[index],
+            id: self.ids[index],
+            colony: self.colony as usize,
+            caste: self.castes[index],
+            carrying_capacity: self.carrying_capacity[index],
+            source_has_more_food: self.source_has_more_food[index],
+            food_source_position: self.food_source_positions[index],
+            recruit_target: self.recruit_targets[index],
+            energy: self.energies[index],
+            age: self.ages[index],
+            lifespan: self.lifespans[index],
+        }
+    }
+
+    // Borrows the ant at `index` in place, for updates that write straight
+    // back into the arrays.
+    pub fn get_mut(&mut self, index: usize) -> AntMut<'_> {
+        AntMut {
+            colony: self.colony as usize,
+            caste: self.castes[index],
+            carrying_capacity: self.carrying_capacity[index],
+            position: &mut self.positions[index],
+            previous_position: &mut self.previous_positions[index],
+            food: &mut self.food[index],
+            returning_to_queen: &mut self.returning_to_queen[index],
+            source_has_more_food: &mut self.source_has_more_food[index],
+            food_source_position: &mut self.food_source_positions[index],
+            recruit_target: &mut self.recruit_targets[index],
+            energy: &mut self.energies[index],
+            lifespan: &mut self.lifespans[index],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Ant> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+}
+
+// One ant's entries in an `AntStore`, borrowed column by column. The id, age
+// and tandem guide are left to the colony.
+pub struct AntMut<'a> {
+    pub colony: usize,
+    pub caste: Caste,
+    pub carrying_capacity: f64,
+    pub position: &'a mut Position,
+    pub previous_position: &'a mut Option<Position>,
+    pub food: &'a mut f64,
+    pub returning_to_queen: &'a mut bool,
+    pub source_has_more_food: &'a mut bool,
+    pub food_source_position: &'a mut Option<Position>,
+    pub recruit_target: &'a mut Option<Position>,
+    pub energy: &'a mut f64,
+    pub lifespan: &'a mut f64,
+}
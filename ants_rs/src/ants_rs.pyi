@@ -1,3 +1,9 @@
+import os
+from typing import Any, Literal
+
+import numpy as np
+import numpy.typing as npt
+
 class Worker:
     def __init__(self, id: int, state: int) -> None: ...
     def perform_task(self) -> None: ...
@@ -7,3 +13,269 @@ class Aggregator:
     def __init__(self) -> None: ...
     def collect_state(self, state: int) -> None: ...
     def get_all_states(self) -> list[int]: ...
+
+Position = tuple[int, int]
+
+class ConfigError(ValueError):
+    field: str | None
+
+class EmptyGridError(ConfigError): ...
+class NotPositiveError(ConfigError): ...
+class NotFiniteError(ConfigError): ...
+class OutOfRangeError(ConfigError): ...
+class VarianceError(ConfigError): ...
+
+class AnimationConfig:
+    figsize: tuple[int, int]
+    step_interval: float
+    fps: int
+
+class FoodAllocationConfig:
+    spawn_chance: float
+    spawn_baseline: int
+    spawn_variance: int
+    value_baseline: float
+    value_variance: float
+
+class AntConfig:
+    carrying_capacity: float
+    initial_lifespan: float
+    lifespan_extension_on_contribution: float
+
+class CombatConfig:
+    strength: list[float]
+    aggression: float
+    loser_death_chance: float
+    winner_death_chance: float
+
+Caste = Literal["forager", "scout", "soldier", "nurse"]
+
+class CasteConfig:
+    carrying_capacity: float | None
+    initial_lifespan: float | None
+    speed: int
+    perception_radius: int | None
+    randomness_factor: float | None
+    strength: float
+
+class CasteDemand:
+    forager: float
+    scout: float
+    soldier: float
+    nurse: float
+    nurses_per_egg: float
+    soldiers_per_casualty: float
+
+class CastesConfig:
+    forager: CasteConfig
+    scout: CasteConfig
+    soldier: CasteConfig
+    nurse: CasteConfig
+    demand: CasteDemand
+    brood_care: float
+
+class RecruitmentConfig:
+    mode: Literal["none", "tandem", "mass"]
+    recruits_per_food: float
+    max_recruits: int
+    nest_radius: int
+
+class EnergyConfig:
+    enabled: bool
+    capacity: float
+    cost_per_move: float
+    metabolic_rate: float
+    hunger_threshold: float
+    food_energy: float
+    colony_consumption_rate: float
+
+class SimulationConfig:
+    grid_size: tuple[int, int]
+    num_ants: int
+    simulation_duration: float
+    food: FoodAllocationConfig
+    ant: AntConfig
+    food_required_to_lay_egg: float
+    egg_gestation_period: float
+    pheromone_initial_intensity: float
+    pheromone_evaporation_rate: float
+    pheromone_evaporation_mode: Literal["eager", "lazy"]
+    pheromone_max_opacity: float
+    randomness_factor: float
+    enable_multiple_pheromones: bool
+    terrain_map: str | None
+    homing_mode: Literal["auto", "greedy", "field"]
+    boundary_mode: Literal["torus", "bounded", "reflecting"]
+    neighborhood: Literal["von_neumann", "moore", "hex"]
+    num_colonies: int
+    food_sharing: Literal["shared", "exclusive"]
+    encounter_mode: Literal["ignore", "avoid", "steal", "fight"]
+    combat: CombatConfig
+    castes: CastesConfig
+    recruitment: RecruitmentConfig
+    energy: EnergyConfig
+    animation: AnimationConfig
+    def __init__(self, config: Any | None = None) -> None: ...
+    @staticmethod
+    def load(path: str | os.PathLike[str] | None = None) -> SimulationConfig: ...
+    @property
+    def perception_radius(self) -> int: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def validate(self) -> None: ...
+
+class Environment:
+    def __init__(self, config: Any, seed: int | None = None) -> None: ...
+    @property
+    def config(self) -> SimulationConfig: ...
+    @property
+    def grid(self) -> npt.NDArray[np.float32]:
+        """Live read-only view of the grid; it changes as the simulation steps."""
+    @property
+    def terrain(self) -> npt.NDArray[np.bool_]: ...
+    def is_wall(self, position: Position) -> bool: ...
+    def set_wall(self, position: Position, wall: bool = True) -> None: ...
+    def load_terrain(self, path: str | os.PathLike[str]) -> None: ...
+    @property
+    def last_update_time(self) -> float: ...
+    def update(self, current_time: float) -> None: ...
+    def spawn_food(self) -> None: ...
+    def evaporate_pheromones(self, current_time: float) -> None: ...
+    def add_pheromone(
+        self, position: Position, pheromone_type: str = "regular", colony: int = 0
+    ) -> None: ...
+    def get_pheromone_level(
+        self, position: Position, pheromone_type: str = "regular", colony: int = 0
+    ) -> float: ...
+    def get_food_amount(self, position: Position) -> float: ...
+    def remove_food(self, position: Position, amount: float) -> None: ...
+    def get_food_positions(self) -> list[Position]: ...
+    def get_pheromone_grid(self) -> npt.NDArray[np.float32]:
+        """Live read-only view of the pheromone layers of `grid`."""
+    def get_territory_map(self) -> npt.NDArray[np.int16]: ...
+    def get_food_positions_within_radius(self, position: Position, radius: int) -> list[Position]: ...
+    def get_closest_food_within_radius(
+        self, position: Position, radius: int
+    ) -> Position | None: ...
+
+class Ant:
+    position: Position
+    previous_position: Position | None
+    food: float
+    returning_to_queen: bool
+    id: int
+    colony: int
+    caste: Caste
+    carrying_capacity: float
+    source_has_more_food: bool
+    food_source_position: Position | None
+    recruit_target: Position | None
+    energy: float
+    age: float
+    lifespan: float
+    def __init__(
+        self,
+        position: Position,
+        id: int,
+        lifespan: float,
+        carrying_capacity: float,
+        colony: int = 0,
+        caste: Caste = "forager",
+    ) -> None: ...
+
+class Queen:
+    position: Position
+    def __init__(self, position: Position) -> None: ...
+
+class Colony:
+    def __init__(
+        self,
+        config: Any,
+        queen: Queen,
+        ants: list[Ant] = ...,
+        seed: int | None = None,
+        id: int = 0,
+    ) -> None: ...
+    @property
+    def id(self) -> int: ...
+    @property
+    def ants(self) -> list[Ant]: ...
+    @property
+    def ant_positions(self) -> list[Position]: ...
+    @property
+    def queen(self) -> Queen: ...
+    @property
+    def food_store(self) -> float: ...
+    @property
+    def food_collected(self) -> float: ...
+    @property
+    def casualties(self) -> int: ...
+    @property
+    def recruits(self) -> int: ...
+    @property
+    def starved(self) -> int: ...
+    @property
+    def food_consumed(self) -> float: ...
+    @property
+    def eggs(self) -> int: ...
+    @property
+    def egg_timers(self) -> list[float]: ...
+    def update_ants(self, environment: Environment, time_delta: float) -> None: ...
+    def update(self, time_delta: float) -> None: ...
+    def __len__(self) -> int: ...
+
+class Simulation:
+    def __init__(self, config: Any | None = None, seed: int | None = None) -> None: ...
+    @classmethod
+    def from_config_or_default(
+        cls, config: Any | None = None, seed: int | None = None
+    ) -> Simulation: ...
+    @property
+    def config(self) -> SimulationConfig: ...
+    @property
+    def environment(self) -> Environment: ...
+    @property
+    def colony(self) -> Colony: ...
+    @property
+    def colonies(self) -> list[Colony]: ...
+    @property
+    def ant_paths(self) -> dict[int, list[Position]]: ...
+    @property
+    def track_paths(self) -> bool: ...
+    @track_paths.setter
+    def track_paths(self, value: bool) -> None: ...
+    @property
+    def recording(self) -> str | None: ...
+    @property
+    def current_time(self) -> float: ...
+    @property
+    def seed(self) -> int: ...
+    def run(self, context: dict[str, Any] | None = None) -> dict[str, Any]: ...
+    def step(self, time_delta: float) -> None: ...
+    def get_stats(self) -> dict[str, Any]: ...
+    def save(self, path: str | os.PathLike[str], format: str | None = None) -> None: ...
+    @classmethod
+    def load(cls, path: str | os.PathLike[str], format: str | None = None) -> Simulation: ...
+    def record_trajectories(
+        self,
+        path: str | os.PathLike[str],
+        format: Literal["ipc", "parquet"] | None = None,
+        batch_size: int = 65536,
+    ) -> None: ...
+    def stop_recording(self) -> str | None: ...
+    def save_frame(self, path: str | os.PathLike[str], cell_size: int = 4) -> None: ...
+    def save_animation(
+        self,
+        path: str | os.PathLike[str],
+        frames: int | None = None,
+        format: Literal["gif", "apng", "frames"] | None = None,
+        cell_size: int = 4,
+        fps: int | None = None,
+    ) -> None: ...
+    def view(self, steps: int | None = None) -> None: ...
+
+def run_batch(
+    configs: list[Any],
+    steps: int | None = None,
+    num_threads: int | None = None,
+    seeds: list[int] | None = None,
+) -> list[dict[str, Any]]: ...
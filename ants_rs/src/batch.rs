@@ -0,0 +1,175 @@
+#[cfg(feature = "python")]
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
+#[cfg(feature = "python")]
+use rayon::prelude::*;
+
+use std::convert::Infallible;
+
+use crate::colony::{update_colonies, Colony};
+use crate::config::SimulationConfig;
+use crate::config_error::ConfigError;
+use crate::environment::Environment;
+#[cfg(feature = "python")]
+use crate::rng;
+use crate::simulation::{populate, RunStats};
+
+// Runs one simulation to completion without touching Python. Like
+// `Simulation.run`, it goes until `simulation_duration` unless `steps` is set.
+// Only loading the terrain map can fail.
+pub fn run_headless(
+    config: &SimulationConfig,
+    seed: u64,
+    steps: Option<usize>,
+) -> Result<RunStats, ConfigError> {
+    let world = populate(config, seed)?;
+    match run_headless_with(config, world, steps, |_, _| Ok::<(), Infallible>(())) {
+        Ok(stats) => Ok(stats),
+        Err(never) => match never {},
+    }
+}
+
+// `run_headless` from a `populate`d environment and colonies, calling
+// `on_step` after every step and stopping at its first error.
+pub fn run_headless_with<E>(
+    config: &SimulationConfig,
+    (mut environment, mut colonies): (Environment, Vec<Colony>),
+    steps: Option<usize>,
+    mut on_step: impl FnMut(&Environment, &[Colony]) -> Result<(), E>,
+) -> Result<RunStats, E> {
+    let time_delta = config.animation.step_interval;
+    let mut current_time = 0.0;
+    for _ in 0..step_count(config, steps) {
+        current_time += time_delta;
+        environment.update(current_time);
+        update_colonies(&mut environment, &mut colonies, time_delta);
+        for colony in &mut colonies {
+            colony.update(time_delta);
+        }
+        on_step(&environment, &colonies)?;
+    }
+    Ok(RunStats::new(&colonies, current_time))
+}
+
+// How many steps `run_headless` takes: `steps` if set, otherwise as many as
+// `Simulation.run` needs to reach `simulation_duration`.
+pub fn step_count(config: &SimulationConfig, steps: Option<usize>) -> usize {
+    if let Some(steps) = steps {
+        return steps;
+    }
+    let mut current_time = 0.0;
+    let mut count = 0;
+    while current_time < config.simulation_duration {
+        current_time += config.animation.step_interval;
+        count += 1;
+    }
+    count
+}
+
+// Runs independent simulations on a thread pool with the GIL released and
+// returns one `get_stats`-style dict per config, without `ant_paths` but with
+// the `seed` that replays the run.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (configs, steps=None, num_threads=None, seeds=None))]
+pub fn run_batch<'py>(
+    py: Python<'py>,
+    configs: Vec<Bound<'py, PyAny>>,
+    steps: Option<usize>,
+    num_threads: Option<usize>,
+    seeds: Option<Vec<u64>>,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    if let Some(seeds) = &seeds {
+        if seeds.len() != configs.len() {
+            return Err(PyValueError::new_err(format!(
+                "got {} seeds for {} configs",
+                seeds.len(),
+                configs.len()
+            )));
+        }
+    }
+    let runs = configs
+        .iter()
+        .enumerate()
+        .map(|(i, config)| {
+            let seed = rng::resolve_seed(seeds.as_ref().map(|seeds| seeds[i]));
+            Ok((SimulationConfig::from_py(config)?, seed))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads.unwrap_or(0))
+        .build()
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+    let results: Vec<(Result<RunStats, ConfigError>, u64)> = py.allow_threads(|| {
+        pool.install(|| {
+            runs.par_iter()
+                .map(|(config, seed)| (run_headless(config, *seed, steps), *seed))
+                .collect()
+        })
+    });
+    results
+        .into_iter()
+        .map(|(stats, seed)| {
+            let stats = stats?.into_py_dict(py)?;
+            stats.set_item("seed", seed)?;
+            Ok(stats)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ant_store::AntId;
+    use crate::config::{EncounterMode, RecruitmentMode};
+    use crate::environment::Position;
+
+    // Every ant's id and position after each step, and the final stats as
+    // JSON, which round-trips floats exactly.
+    fn run(config: &SimulationConfig, seed: u64) -> (Vec<Vec<(AntId, Position)>>, String) {
+        let mut trajectories = Vec::new();
+        let world = populate(config, seed).unwrap();
+        let stats = run_headless_with(config, world, Some(300), |_, colonies| {
+            trajectories.push(
+                colonies
+                    .iter()
+                    .flat_map(|colony| {
+                        colony
+                            .ants
+                            .ids
+                            .iter()
+                            .copied()
+                            .zip(colony.ants.positions.iter().copied())
+                    })
+                    .collect(),
+            );
+            Ok::<(), Infallible>(())
+        })
+        .unwrap();
+        (trajectories, serde_json::to_string(&stats).unwrap())
+    }
+
+    #[test]
+    fn same_seed_gives_same_run() {
+        let mut config = SimulationConfig {
+            grid_size: (40, 30),
+            num_ants: 20,
+            num_colonies: 2,
+            encounter_mode: EncounterMode::Fight,
+            ..SimulationConfig::default()
+        };
+        config.castes.demand.scout = 0.5;
+        config.castes.demand.soldier = 0.5;
+        config.recruitment.mode = RecruitmentMode::Tandem;
+        config.energy.enabled = true;
+
+        let first = run(&config, 7);
+        assert_eq!(first, run(&config, 7));
+        assert!(first.0.iter().any(|step| !step.is_empty()));
+        assert_ne!(first, run(&config, 8));
+    }
+}
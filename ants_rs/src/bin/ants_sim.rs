@@ -0,0 +1,112 @@
+// Runs the Rust simulation headless and prints the `get_stats` summary as
+// JSON, without matplotlib, Prefect or a Python interpreter.
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use ants_rs::batch::{run_headless_with, step_count};
+use ants_rs::render::{self, AnimationFormat, AnimationWriter};
+use ants_rs::rng;
+use ants_rs::settings;
+use ants_rs::simulation::populate;
+use ants_rs::viewer::{self, Headless, ViewOptions};
+
+#[derive(Parser)]
+#[command(name = "ants-sim", about = "Run the ant colony simulation headless")]
+struct Args {
+    /// TOML, YAML or JSON config file with the fields of
+    /// `config.SimulationConfig`; missing fields keep their defaults, and
+    /// `ANTS_` environment variables override both.
+    config: Option<PathBuf>,
+
+    /// Number of steps to run instead of stopping at `simulation_duration`.
+    #[arg(long)]
+    steps: Option<usize>,
+
+    /// Seed for a reproducible run; a random one is picked and reported
+    /// otherwise.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Write the stats to this file instead of stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Also render every step to this animation: a `.gif`, an APNG (`.png`
+    /// or `.apng`) or, for any other path, a directory of PNG frames.
+    #[arg(long)]
+    animation: Option<PathBuf>,
+
+    /// Pixels per grid cell in the animation.
+    #[arg(long, default_value_t = render::DEFAULT_CELL_SIZE)]
+    cell_size: u32,
+
+    /// Watch the run live in the terminal instead (space pauses, n steps,
+    /// +/- change the speed, q quits); the stats are printed on exit.
+    #[arg(long, conflicts_with = "animation")]
+    view: bool,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("ants-sim: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let config = settings::load(args.config.as_deref()).map_err(|e| e.to_string())?;
+    let seed = rng::resolve_seed(args.seed);
+
+    let stats = match &args.animation {
+        None if args.view => {
+            let mut source = Headless::new(&config, seed).map_err(|e| e.to_string())?;
+            viewer::run(&mut source, &ViewOptions::new(&config, args.steps))
+                .map_err(|e| e.to_string())?;
+            source.stats()
+        }
+        Some(path) => {
+            let format = AnimationFormat::resolve(None, path).expect("inferred from the path");
+            let path_error = |e: std::io::Error| format!("{}: {e}", path.display());
+            let mut writer = AnimationWriter::create(
+                path,
+                format,
+                config.grid_size.0 as u32 * args.cell_size,
+                config.grid_size.1 as u32 * args.cell_size,
+                config.animation.fps,
+                step_count(&config, args.steps) as u32,
+            )
+            .map_err(path_error)?;
+            let world = populate(&config, seed).map_err(|e| e.to_string())?;
+            let stats = run_headless_with(&config, world, args.steps, |environment, colonies| {
+                writer.push(&render::render(environment, colonies, args.cell_size))
+            })
+            .map_err(path_error)?;
+            writer.finish().map_err(path_error)?;
+            stats
+        }
+        None => {
+            let world = populate(&config, seed).map_err(|e| e.to_string())?;
+            run_headless_with(&config, world, args.steps, |_, _| Ok::<(), String>(()))?
+        }
+    };
+
+    // Same keys as the dicts from `run_batch`
+    let mut summary = serde_json::to_value(stats).map_err(|e| e.to_string())?;
+    summary["seed"] = seed.into();
+    let summary = serde_json::to_string_pretty(&summary).map_err(|e| e.to_string())?;
+    match &args.output {
+        Some(path) => {
+            fs::write(path, summary + "\n").map_err(|e| format!("{}: {e}", path.display()))
+        }
+        None => {
+            println!("{summary}");
+            Ok(())
+        }
+    }
+}
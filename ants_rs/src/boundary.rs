@@ -0,0 +1,170 @@
+// Grid edges under each `BoundaryMode`. Everything that moves, measures or
+// looks across the grid goes through here instead of wrapping with `%`.
+use crate::config::{BoundaryMode, Neighborhood};
+use crate::environment::{manhattan_distance, Position};
+
+impl BoundaryMode {
+    // Where a move by (dx, dy) from `position` ends up, or `None` if it runs
+    // into the edge of a bounded grid.
+    pub fn step(
+        self,
+        position: Position,
+        dx: i64,
+        dy: i64,
+        grid_size: (usize, usize),
+    ) -> Option<Position> {
+        let (x, y) = (position.0 as i64 + dx, position.1 as i64 + dy);
+        let (grid_width, grid_height) = (grid_size.0 as i64, grid_size.1 as i64);
+        match self {
+            BoundaryMode::Torus => Some((
+                x.rem_euclid(grid_width) as usize,
+                y.rem_euclid(grid_height) as usize,
+            )),
+            BoundaryMode::Bounded => inside(x, y, grid_size),
+            BoundaryMode::Reflecting => Some((
+                reflect(x, grid_width) as usize,
+                reflect(y, grid_height) as usize,
+            )),
+        }
+    }
+
+    // The cell at offset (dx, dy), for scanning an area around `position`:
+    // wrapped on the torus and cut off at the edges otherwise, so nothing is
+    // seen twice.
+    pub fn locate(
+        self,
+        position: Position,
+        dx: i64,
+        dy: i64,
+        grid_size: (usize, usize),
+    ) -> Option<Position> {
+        match self {
+            BoundaryMode::Torus => self.step(position, dx, dy, grid_size),
+            BoundaryMode::Bounded | BoundaryMode::Reflecting => {
+                inside(position.0 as i64 + dx, position.1 as i64 + dy, grid_size)
+            }
+        }
+    }
+
+    // The shortest (dx, dy) from `from` to `to`, which on the torus may cross
+    // an edge the way `Ant.move_towards` would. Each axis is shortened on its
+    // own, which is also shortest on the square grids; on the hex grid the
+    // other way round an axis can be shorter overall.
+    pub fn delta(
+        self,
+        from: Position,
+        to: Position,
+        grid_size: (usize, usize),
+        neighborhood: Neighborhood,
+    ) -> (i64, i64) {
+        let dx = to.0 as i64 - from.0 as i64;
+        let dy = to.1 as i64 - from.1 as i64;
+        match self {
+            BoundaryMode::Torus => {
+                let (grid_width, grid_height) = (grid_size.0 as i64, grid_size.1 as i64);
+                let delta = (shortest(dx, grid_width), shortest(dy, grid_height));
+                if neighborhood != Neighborhood::Hex {
+                    return delta;
+                }
+                let (dx, dy) = delta;
+                let xs = [dx, dx - dx.signum() * grid_width];
+                let ys = [dy, dy - dy.signum() * grid_height];
+                xs.into_iter()
+                    .flat_map(|dx| ys.map(|dy| (dx, dy)))
+                    .fold(delta, |best, (dx, dy)| {
+                        if neighborhood.norm(dx, dy) < neighborhood.norm(best.0, best.1) {
+                            (dx, dy)
+                        } else {
+                            best
+                        }
+                    })
+            }
+            BoundaryMode::Bounded | BoundaryMode::Reflecting => (dx, dy),
+        }
+    }
+
+    // Steps between two cells, in the unit perception radii use.
+    pub fn distance(
+        self,
+        pos1: Position,
+        pos2: Position,
+        grid_size: (usize, usize),
+        neighborhood: Neighborhood,
+    ) -> usize {
+        match (self, neighborhood) {
+            (BoundaryMode::Torus, Neighborhood::VonNeumann) => {
+                manhattan_distance(pos1, pos2, grid_size)
+            }
+            _ => {
+                let (dx, dy) = self.delta(pos1, pos2, grid_size, neighborhood);
+                neighborhood.norm(dx, dy)
+            }
+        }
+    }
+
+    pub fn wraps(self) -> bool {
+        self == BoundaryMode::Torus
+    }
+}
+
+fn inside(x: i64, y: i64, grid_size: (usize, usize)) -> Option<Position> {
+    let inside_x = (0..grid_size.0 as i64).contains(&x);
+    let inside_y = (0..grid_size.1 as i64).contains(&y);
+    (inside_x && inside_y).then_some((x as usize, y as usize))
+}
+
+// Folds a coordinate back into 0..size as if the edges were mirrors, so one
+// step past the last cell lands on the one before it.
+fn reflect(value: i64, size: i64) -> i64 {
+    if size == 1 {
+        return 0;
+    }
+    let period = 2 * (size - 1);
+    let folded = value.rem_euclid(period);
+    if folded < size {
+        folded
+    } else {
+        period - folded
+    }
+}
+
+// Same as the `(t - x + size) % size`, then `- size` past half, in
+// `Ant.move_towards`.
+fn shortest(delta: i64, size: i64) -> i64 {
+    let delta = delta.rem_euclid(size);
+    if delta > size / 2 {
+        delta - size
+    } else {
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflecting_edges_bounce_back_one_cell() {
+        let grid_size = (5, 4);
+        let step = |position, dx, dy| {
+            BoundaryMode::Reflecting
+                .step(position, dx, dy, grid_size)
+                .unwrap()
+        };
+        // Left, right, top and bottom
+        assert_eq!(step((0, 2), -1, 0), (1, 2));
+        assert_eq!(step((4, 2), 1, 0), (3, 2));
+        assert_eq!(step((2, 0), 0, -1), (2, 1));
+        assert_eq!(step((2, 3), 0, 1), (2, 2));
+        // Both axes at once in a corner
+        assert_eq!(step((0, 0), -1, -1), (1, 1));
+        assert_eq!(step((4, 3), 1, 1), (3, 2));
+        // Inside the grid nothing changes
+        assert_eq!(step((2, 2), 1, -1), (3, 1));
+        // A grid one cell wide has nowhere to bounce to
+        assert_eq!(
+            BoundaryMode::Reflecting.step((0, 1), 1, 0, (1, 4)),
+            Some((0, 1))
+        );
+    }
+}
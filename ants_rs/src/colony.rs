@@ -0,0 +1,791 @@
+use std::borrow::BorrowMut;
+
+use crate::ant::Ant;
+use crate::ant_store::{AntStore, ColonyFull};
+use crate::config::{Caste, EncounterMode, HomingMode, RecruitmentMode, SimulationConfig};
+use crate::encounter::{self, Occupancy};
+use crate::environment::{Environment, Position};
+use crate::homing::DistanceField;
+use crate::rng::{self, SimRng};
+#[cfg(feature = "python")]
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// Mirrors `models.Queen`.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Queen {
+    pub position: Position,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Queen {
+    #[new]
+    fn new(position: Position) -> Queen {
+        Queen { position }
+    }
+}
+
+// Mirrors `models.Colony`, plus the per-ant loop from `Simulation.step`.
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Serialize, Deserialize)]
+pub struct Colony {
+    // Written to snapshots once for the whole simulation; see `Snapshot::read`
+    #[serde(skip)]
+    pub config: SimulationConfig,
+    // Index among the simulation's colonies, which picks its pheromone
+    // layers and tags its ant ids
+    pub id: usize,
+    pub ants: AntStore,
+    pub queen: Queen,
+    pub food_store: f64,
+    // All the food ever delivered to the queen, before eggs use it up
+    pub food_collected: f64,
+    // Ants lost in fights with rivals
+    pub casualties: usize,
+    // Nestmates recruited to food sources
+    pub recruits: usize,
+    // Ants that ran out of energy, and the food eaten from the store by
+    // hungry ants and the colony's upkeep
+    pub starved: usize,
+    pub food_consumed: f64,
+    pub eggs: usize,
+    pub egg_timers: Vec<f64>,
+    rng: SimRng,
+    // Derived from the terrain and the queen's position, so rebuilt rather
+    // than saved
+    #[serde(skip)]
+    homing: Option<DistanceField>,
+    #[serde(skip)]
+    homing_key: Option<(Position, u64)>,
+}
+
+impl Colony {
+    pub fn new(
+        config: SimulationConfig,
+        id: usize,
+        queen: Queen,
+        ants: Vec<Ant>,
+        seed: u64,
+    ) -> Result<Colony, ColonyFull> {
+        let mut store = AntStore::with_capacity(id as u8, ants.len());
+        for ant in ants {
+            store.insert(Ant {
+                energy: config.energy.capacity,
+                ..ant
+            })?;
+        }
+        Ok(Colony {
+            config,
+            id,
+            ants: store,
+            queen,
+            food_store: 0.0,
+            food_collected: 0.0,
+            casualties: 0,
+            recruits: 0,
+            starved: 0,
+            food_consumed: 0.0,
+            eggs: 0,
+            egg_timers: Vec::new(),
+            rng: rng::stream(seed, rng::colony_stream(id)),
+            homing: None,
+            homing_key: None,
+        })
+    }
+
+    // Recomputes the homing field when the queen or the terrain has changed
+    // since it was built.
+    fn refresh_homing(&mut self, environment: &Environment) {
+        let key = (self.queen.position, environment.terrain_revision);
+        if self.homing_key == Some(key) {
+            return;
+        }
+        self.homing_key = Some(key);
+        let use_field = match self.config.homing_mode {
+            HomingMode::Greedy => false,
+            HomingMode::Field => true,
+            HomingMode::Auto => environment.terrain.walls.iter().any(|&wall| wall),
+        };
+        self.homing = use_field.then(|| {
+            DistanceField::compute(
+                &environment.terrain,
+                self.queen.position,
+                self.config.boundary_mode,
+                self.config.neighborhood,
+            )
+        });
+    }
+
+    // Ages every ant, drops the ones that died of old age, then updates the
+    // survivors once each.
+    pub fn update_ants(&mut self, environment: &mut Environment, time_delta: f64) {
+        self.age_ants(time_delta);
+        self.feed_ants(time_delta);
+        self.refresh_homing(environment);
+        for index in 0..self.ants.len() {
+            self.update_ant(index, environment);
+        }
+        self.remove_starved();
+    }
+
+    fn age_ants(&mut self, time_delta: f64) {
+        // Only touches the age and lifespan arrays. A removal swaps the last
+        // ant into `index`, which is then aged on the next iteration.
+        let mut index = 0;
+        while index < self.ants.len() {
+            self.ants.ages[index] += time_delta;
+            if self.ants.ages[index] >= self.ants.lifespans[index] {
+                self.ants.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    // Under `energy.enabled`: burns the ants' resting metabolism, feeds the
+    // hungry from their load and, at the nest, from the store, sends those
+    // with nothing to eat home, and removes the ones that ran out of energy.
+    // Like `age_ants`, a removal swaps the last ant into `index`.
+    fn feed_ants(&mut self, time_delta: f64) {
+        let energy = &self.config.energy;
+        if !energy.enabled {
+            return;
+        }
+        let hungry = energy.hunger_threshold * energy.capacity;
+        let ants = &mut self.ants;
+        let mut index = 0;
+        while index < ants.len() {
+            ants.energies[index] -= energy.metabolic_rate * time_delta;
+            if ants.energies[index] < hungry {
+                let appetite = (energy.capacity - ants.energies[index]) / energy.food_energy;
+                let mut eaten = appetite.min(ants.food[index]);
+                ants.food[index] -= eaten;
+                let at_nest = ants.positions[index] == self.queen.position;
+                if at_nest {
+                    let from_store = (appetite - eaten).min(self.food_store);
+                    self.food_store -= from_store;
+                    self.food_consumed += from_store;
+                    eaten += from_store;
+                }
+                ants.energies[index] += eaten * energy.food_energy;
+                if ants.energies[index] < hungry && ants.food[index] <= 0.0 && !at_nest {
+                    // Home to eat, not to deliver: no rich trail on the way
+                    // and no recruiting on arrival
+                    ants.returning_to_queen[index] = true;
+                    ants.source_has_more_food[index] = false;
+                    ants.food_source_positions[index] = None;
+                }
+            }
+            if ants.energies[index] <= 0.0 {
+                ants.remove(index);
+                self.starved += 1;
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    // Moves the ant at `index` as many times as its caste's speed allows.
+    // Nurses stay at the nest with the brood. A forager that delivers food
+    // from a source with more left recruits nestmates to it. An ant that
+    // spends the last of its energy stops where it is, to be removed by
+    // `remove_starved` once everyone has moved.
+    fn update_ant(&mut self, index: usize, environment: &mut Environment) {
+        let caste = self.ants.castes[index];
+        if caste == Caste::Nurse {
+            return;
+        }
+        let leader = self.tandem_leader(index);
+        let mut ant = self.ants.get_mut(index);
+        let food_store = self.food_store;
+        let mut rich_source = None;
+        for _ in 0..self.config.castes.get(caste).speed {
+            let source = ant
+                .food_source_position
+                .filter(|_| *ant.returning_to_queen && *ant.source_has_more_food);
+            let start = *ant.position;
+            ant.update(
+                environment,
+                self.queen.position,
+                self.homing.as_ref(),
+                leader,
+                &mut self.food_store,
+                &self.config,
+                &mut self.rng,
+            );
+            if !*ant.returning_to_queen {
+                rich_source = rich_source.or(source);
+            }
+            // Only a step that gets somewhere costs energy, not waiting
+            if self.config.energy.enabled && *ant.position != start {
+                *ant.energy -= self.config.energy.cost_per_move;
+                if *ant.energy <= 0.0 {
+                    rich_source = None;
+                    break;
+                }
+            }
+        }
+        self.food_collected += self.food_store - food_store;
+        if let Some(source) = rich_source {
+            self.recruit(index, source, environment);
+        }
+    }
+
+    // Under `energy.enabled`: removes the ants that ran out of energy moving
+    // this step. Like `age_ants`, a removal swaps the last ant into `index`.
+    fn remove_starved(&mut self) {
+        if !self.config.energy.enabled {
+            return;
+        }
+        let mut index = 0;
+        while index < self.ants.len() {
+            if self.ants.energies[index] <= 0.0 {
+                self.ants.remove(index);
+                self.starved += 1;
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    // Where the nestmate leading the ant at `index` on a tandem run is, while
+    // it is still on its way; the guide is dropped once it arrives or dies.
+    // `update_ant` looks it up once for all of the recruit's moves, as the
+    // leader only moves on its own turn. A recruit that comes before its
+    // leader in the store so trails it by one step.
+    fn tandem_leader(&mut self, index: usize) -> Option<Position> {
+        let guide = self.ants.guides[index]?;
+        match self.ants.index_of(guide) {
+            Some(leader) if self.ants.recruit_targets[leader].is_some() => {
+                Some(self.ants.positions[leader])
+            }
+            _ => {
+                self.ants.guides[index] = None;
+                None
+            }
+        }
+    }
+
+    // Sends idle foragers near the nest, closest first, to `source`, more of
+    // them the more food is left there, and the recruiter back with them.
+    fn recruit(&mut self, recruiter: usize, source: Position, environment: &Environment) {
+        let recruitment = &self.config.recruitment;
+        let richness = environment.food_amount(source) as f64;
+        if richness <= 0.0 {
+            return;
+        }
+        let wanted = match recruitment.mode {
+            RecruitmentMode::None => return,
+            RecruitmentMode::Tandem => {
+                (self.rng.gen::<f64>() < richness * recruitment.recruits_per_food) as usize
+            }
+            RecruitmentMode::Mass => (richness * recruitment.recruits_per_food).ceil() as usize,
+        }
+        .min(recruitment.max_recruits);
+        if wanted == 0 {
+            return;
+        }
+
+        let grid_size = self.config.grid_size;
+        let boundary = self.config.boundary_mode;
+        let neighborhood = self.config.neighborhood;
+        let mut idle: Vec<(usize, usize)> = (0..self.ants.len())
+            .filter(|&index| index != recruiter && self.is_idle(index))
+            .map(|index| {
+                let position = self.ants.positions[index];
+                let distance =
+                    boundary.distance(self.queen.position, position, grid_size, neighborhood);
+                (distance, index)
+            })
+            .filter(|&(distance, _)| distance <= recruitment.nest_radius)
+            .collect();
+        if idle.is_empty() {
+            return;
+        }
+        idle.sort_unstable();
+
+        let guide = (recruitment.mode == RecruitmentMode::Tandem).then(|| self.ants.ids[recruiter]);
+        for &(_, index) in idle.iter().take(wanted) {
+            self.ants.recruit_targets[index] = Some(source);
+            self.ants.guides[index] = guide;
+            self.recruits += 1;
+        }
+        self.ants.recruit_targets[recruiter] = Some(source);
+    }
+
+    // Foraging empty-handed with nowhere in particular to go.
+    fn is_idle(&self, index: usize) -> bool {
+        let ants = &self.ants;
+        ants.castes[index] != Caste::Nurse
+            && !ants.returning_to_queen[index]
+            && ants.food[index] == 0.0
+            && ants.recruit_targets[index].is_none()
+            && ants.guides[index].is_none()
+    }
+
+    pub fn update(&mut self, time_delta: f64) {
+        self.hatch_eggs(time_delta);
+        self.pay_upkeep(time_delta);
+        self.lay_eggs();
+    }
+
+    // The queen and brood's share of the store under `energy.enabled`:
+    // `colony_consumption_rate` per ant per unit of time, while it lasts.
+    fn pay_upkeep(&mut self, time_delta: f64) {
+        let energy = &self.config.energy;
+        if !energy.enabled {
+            return;
+        }
+        let upkeep = energy.colony_consumption_rate * self.ants.len() as f64 * time_delta;
+        let upkeep = upkeep.min(self.food_store);
+        self.food_store -= upkeep;
+        self.food_consumed += upkeep;
+    }
+
+    fn lay_eggs(&mut self) {
+        let food_required = self.config.food_required_to_lay_egg;
+        let eggs_to_lay = (self.food_store / food_required).floor() as usize;
+        self.food_store = self.food_store.rem_euclid(food_required);
+        self.eggs += eggs_to_lay;
+        self.egg_timers
+            .extend(std::iter::repeat_n(0.0, eggs_to_lay));
+    }
+
+    fn hatch_eggs(&mut self, time_delta: f64) {
+        let gestation_period = self.config.egg_gestation_period;
+        let development = time_delta * (1.0 + self.brood_care());
+        for timer in self.egg_timers.iter_mut() {
+            *timer += development;
+        }
+        let hatched = self
+            .egg_timers
+            .iter()
+            .filter(|&&t| t >= gestation_period)
+            .count();
+        self.egg_timers.retain(|&t| t < gestation_period);
+        for hatching in 0..hatched {
+            // The store assigns the id
+            let caste = self.choose_caste();
+            let new_ant = Ant {
+                caste,
+                energy: self.config.energy.capacity,
+                ..Ant::new(
+                    self.queen.position,
+                    0,
+                    self.config.initial_lifespan_of(caste),
+                    self.config.carrying_capacity_of(caste),
+                )
+            };
+            if self.ants.insert(new_ant).is_err() {
+                // No room in the colony; the rest wait, ready to hatch
+                self.egg_timers
+                    .extend(std::iter::repeat_n(gestation_period, hatched - hatching));
+                break;
+            }
+            self.eggs -= 1;
+        }
+    }
+
+    // Extra egg development speed from the nurses, in proportion to the
+    // nurses per egg, up to one each.
+    fn brood_care(&self) -> f64 {
+        if self.egg_timers.is_empty() {
+            return 0.0;
+        }
+        let nurses = self.caste_counts()[Caste::Nurse as usize] as f64;
+        let coverage = (nurses / self.egg_timers.len() as f64).min(1.0);
+        self.config.castes.brood_care * coverage
+    }
+
+    // The caste furthest below its `castes.demand` target, counting the
+    // hatchling in the colony's size.
+    fn choose_caste(&self) -> Caste {
+        let demand = &self.config.castes.demand;
+        let counts = self.caste_counts();
+        let total_weight: f64 = Caste::ALL.iter().map(|&caste| demand.weight(caste)).sum();
+        let colony_size = (self.ants.len() + 1) as f64;
+        let mut chosen = Caste::Forager;
+        let mut largest_shortfall = f64::NEG_INFINITY;
+        for caste in Caste::ALL {
+            let share = if total_weight > 0.0 {
+                colony_size * demand.weight(caste) / total_weight
+            } else {
+                0.0
+            };
+            let target = match caste {
+                Caste::Nurse => share.max(demand.nurses_per_egg * self.eggs as f64),
+                Caste::Soldier => share.max(demand.soldiers_per_casualty * self.casualties as f64),
+                Caste::Forager | Caste::Scout => share,
+            };
+            let shortfall = target - counts[caste as usize] as f64;
+            if shortfall > largest_shortfall {
+                chosen = caste;
+                largest_shortfall = shortfall;
+            }
+        }
+        chosen
+    }
+
+    // Live ants per caste, indexed like `Caste::ALL`.
+    pub fn caste_counts(&self) -> [usize; 4] {
+        let mut counts = [0; 4];
+        for &caste in &self.ants.castes {
+            counts[caste as usize] += 1;
+        }
+        counts
+    }
+}
+
+// `update_ants` for every colony sharing `environment`. The colonies take
+// turns ant by ant, the first ant of each, then the second of each and so
+// on, so none of them always gets to the food first; then rivals that ended
+// up together meet.
+pub fn update_colonies<C: BorrowMut<Colony>>(
+    environment: &mut Environment,
+    colonies: &mut [C],
+    time_delta: f64,
+) {
+    let mode = environment.config.encounter_mode;
+    environment.occupancy = (colonies.len() > 1 && mode == EncounterMode::Avoid)
+        .then(|| Occupancy::build(environment.config.grid_size, colonies));
+    for colony in colonies.iter_mut() {
+        let colony = colony.borrow_mut();
+        colony.age_ants(time_delta);
+        colony.feed_ants(time_delta);
+        colony.refresh_homing(environment);
+    }
+    let most_ants = colonies
+        .iter()
+        .map(|colony| colony.borrow().ants.len())
+        .max()
+        .unwrap_or(0);
+    for index in 0..most_ants {
+        for colony in colonies.iter_mut() {
+            let colony = colony.borrow_mut();
+            if index < colony.ants.len() {
+                colony.update_ant(index, environment);
+            }
+        }
+    }
+    for colony in colonies.iter_mut() {
+        colony.borrow_mut().remove_starved();
+    }
+    encounter::resolve(environment, colonies);
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Colony {
+    // `id` is the colony's index, below `config.num_colonies`.
+    #[new]
+    #[pyo3(signature = (config, queen, ants=Vec::new(), seed=None, id=0))]
+    fn py_new(
+        config: &Bound<'_, PyAny>,
+        queen: Queen,
+        ants: Vec<Ant>,
+        seed: Option<u64>,
+        id: usize,
+    ) -> PyResult<Self> {
+        let config = SimulationConfig::from_py(config)?;
+        if id >= config.num_colonies {
+            return Err(PyValueError::new_err(format!(
+                "colony id {id} is out of range for {} colonies",
+                config.num_colonies
+            )));
+        }
+        Colony::new(config, id, queen, ants, rng::resolve_seed(seed))
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    #[getter(id)]
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    // Snapshots of the live ants; the ids passed to the constructor are
+    // replaced by ones the colony assigns.
+    #[getter(ants)]
+    fn get_ants(&self) -> Vec<Ant> {
+        self.ants.iter().collect()
+    }
+
+    // Just the positions, without building an `Ant` per ant.
+    #[getter(ant_positions)]
+    fn get_ant_positions(&self) -> Vec<Position> {
+        self.ants.positions.clone()
+    }
+
+    #[getter(queen)]
+    fn get_queen(&self) -> Queen {
+        self.queen.clone()
+    }
+
+    #[getter(food_store)]
+    fn get_food_store(&self) -> f64 {
+        self.food_store
+    }
+
+    #[getter(food_collected)]
+    fn get_food_collected(&self) -> f64 {
+        self.food_collected
+    }
+
+    #[getter(casualties)]
+    fn get_casualties(&self) -> usize {
+        self.casualties
+    }
+
+    #[getter(recruits)]
+    fn get_recruits(&self) -> usize {
+        self.recruits
+    }
+
+    #[getter(starved)]
+    fn get_starved(&self) -> usize {
+        self.starved
+    }
+
+    #[getter(food_consumed)]
+    fn get_food_consumed(&self) -> f64 {
+        self.food_consumed
+    }
+
+    #[getter(eggs)]
+    fn get_eggs(&self) -> usize {
+        self.eggs
+    }
+
+    #[getter(egg_timers)]
+    fn get_egg_timers(&self) -> Vec<f64> {
+        self.egg_timers.clone()
+    }
+
+    // Advances the whole colony in one call instead of one `Ant.update` per ant.
+    #[pyo3(name = "update_ants")]
+    fn py_update_ants(&mut self, mut environment: PyRefMut<'_, Environment>, time_delta: f64) {
+        self.update_ants(&mut environment, time_delta);
+    }
+
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, time_delta: f64) {
+        self.update(time_delta);
+    }
+
+    fn __len__(&self) -> usize {
+        self.ants.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BoundaryMode, Neighborhood};
+    use crate::simulation::populate;
+    use crate::terrain::Terrain;
+
+    fn hungry_colony(config: &SimulationConfig, position: Position) -> Colony {
+        let ant = Ant::new(position, 0, 1000.0, 10.0);
+        let queen = Queen { position: (5, 5) };
+        let mut colony = Colony::new(config.clone(), 0, queen, vec![ant], 0).unwrap();
+        colony.ants.energies[0] = 0.2 * config.energy.capacity;
+        colony
+    }
+
+    fn energy_config() -> SimulationConfig {
+        let mut config = SimulationConfig {
+            grid_size: (10, 10),
+            ..SimulationConfig::default()
+        };
+        config.energy.enabled = true;
+        config
+    }
+
+    #[test]
+    fn ants_going_home_to_eat_forget_their_source() {
+        let config = energy_config();
+        let mut colony = hungry_colony(&config, (1, 1));
+        colony.ants.source_has_more_food[0] = true;
+        colony.ants.food_source_positions[0] = Some((0, 0));
+
+        colony.feed_ants(0.1);
+        assert!(colony.ants.returning_to_queen[0]);
+        assert!(!colony.ants.source_has_more_food[0]);
+        assert_eq!(colony.ants.food_source_positions[0], None);
+    }
+
+    #[test]
+    fn waiting_costs_no_energy() {
+        let config = energy_config();
+        let mut terrain = Terrain::open(config.grid_size);
+        for x in 0..3 {
+            for y in 0..3 {
+                terrain.walls[[x, y]] = (x, y) != (1, 1);
+            }
+        }
+        let mut environment = Environment::new(config.clone(), 0, terrain);
+        let mut colony = hungry_colony(&config, (1, 1));
+        let energy = colony.ants.energies[0];
+
+        colony.update_ant(0, &mut environment);
+        assert_eq!(colony.ants.positions[0], (1, 1));
+        assert_eq!(colony.ants.energies[0], energy);
+    }
+
+    #[test]
+    fn hatchlings_follow_the_caste_demand_and_nurses_stay_home() {
+        let mut config = SimulationConfig {
+            grid_size: (30, 30),
+            num_ants: 1,
+            ..SimulationConfig::default()
+        };
+        let demand = &mut config.castes.demand;
+        demand.forager = 2.0;
+        demand.scout = 1.0;
+        demand.nurse = 1.0;
+        let (mut environment, mut colonies) = populate(&config, 3).unwrap();
+        colonies[0].food_store = 39.0 * config.food_required_to_lay_egg;
+
+        let time_delta = config.animation.step_interval;
+        let mut current_time = 0.0;
+        for _ in 0..300 {
+            current_time += time_delta;
+            environment.update(current_time);
+            update_colonies(&mut environment, &mut colonies, time_delta);
+            let colony = &mut colonies[0];
+            colony.update(time_delta);
+            for (&caste, &position) in colony.ants.castes.iter().zip(&colony.ants.positions) {
+                if caste == Caste::Nurse {
+                    assert_eq!(position, colony.queen.position);
+                }
+            }
+        }
+
+        let colony = &colonies[0];
+        assert!(
+            colony.eggs == 0 && colony.ants.len() >= 40,
+            "{}",
+            colony.ants.len()
+        );
+        let counts = colony.caste_counts();
+        let size = colony.ants.len() as f64;
+        for (caste, weight) in [
+            (Caste::Forager, 0.5),
+            (Caste::Scout, 0.25),
+            (Caste::Soldier, 0.0),
+            (Caste::Nurse, 0.25),
+        ] {
+            let count = counts[caste as usize] as f64;
+            assert!(
+                (count - size * weight).abs() <= 1.0,
+                "{caste:?}: {count} of {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn tandem_recruits_follow_their_leader_to_the_source() {
+        let mut config = SimulationConfig {
+            grid_size: (20, 20),
+            boundary_mode: BoundaryMode::Bounded,
+            ..SimulationConfig::default()
+        };
+        config.recruitment.mode = RecruitmentMode::Tandem;
+        config.recruitment.recruits_per_food = 1.0;
+        let source = (16, 10);
+        let mut environment = Environment::new(config.clone(), 0, Terrain::open(config.grid_size));
+        environment.drop_food(source, 50.0);
+
+        let mut recruiter = Ant::new((11, 10), 0, 1000.0, 10.0);
+        recruiter.food = 10.0;
+        recruiter.returning_to_queen = true;
+        recruiter.food_source_position = Some(source);
+        recruiter.source_has_more_food = true;
+        let idle = Ant::new((10, 11), 0, 1000.0, 10.0);
+        let queen = Queen { position: (10, 10) };
+        let mut colony = Colony::new(config.clone(), 0, queen, vec![recruiter, idle], 0).unwrap();
+        let leader = colony.ants.ids[0];
+
+        // Delivering food from a source with more left recruits the idle ant
+        colony.update_ants(&mut environment, 0.1);
+        assert_eq!(colony.recruits, 1);
+        assert_eq!(colony.ants.recruit_targets[1], Some(source));
+        assert_eq!(colony.ants.guides[1], Some(leader));
+
+        let mut steps = 0;
+        while colony.ants.recruit_targets[1].is_some() {
+            assert!(steps < 20, "never got to the source");
+            colony.update_ants(&mut environment, 0.1);
+            steps += 1;
+            // One step behind the leader at most while it is on its way
+            if colony.ants.guides[1].is_some() {
+                let (leader, recruit) = (colony.ants.positions[0], colony.ants.positions[1]);
+                assert!(
+                    leader.0.abs_diff(recruit.0) + leader.1.abs_diff(recruit.1) <= 1,
+                    "{recruit:?} fell behind {leader:?}"
+                );
+            }
+        }
+        assert!(
+            colony.ants.positions[1] == source || colony.ants.food[1] > 0.0,
+            "stopped at {:?}",
+            colony.ants.positions[1]
+        );
+    }
+
+    #[test]
+    fn ants_that_spend_their_last_energy_moving_are_gone_that_step() {
+        let mut config = energy_config();
+        config.castes.forager.speed = 3;
+        let mut environment = Environment::new(config.clone(), 0, Terrain::open(config.grid_size));
+        let mut colony = hungry_colony(&config, (1, 1));
+        // Enough for the first move only, and too little to eat before it
+        colony.ants.energies[0] = config.energy.cost_per_move;
+        colony.config.energy.hunger_threshold = 0.0;
+
+        colony.update_ants(&mut environment, 0.1);
+        assert!(colony.ants.is_empty());
+        assert_eq!(colony.starved, 1);
+    }
+
+    #[test]
+    fn homing_ants_go_around_walls_that_touch_at_a_corner() {
+        let config = SimulationConfig {
+            grid_size: (10, 10),
+            boundary_mode: BoundaryMode::Bounded,
+            neighborhood: Neighborhood::VonNeumann,
+            ..SimulationConfig::default()
+        };
+        // A diagonal of walls between the ant and the queen, corner to corner
+        // all the way but for a gap at the top right
+        let mut terrain = Terrain::open(config.grid_size);
+        for x in 0..8 {
+            terrain.walls[[x, 9 - x]] = true;
+        }
+        let mut environment = Environment::new(config.clone(), 0, terrain.clone());
+        let mut ant = Ant::new((9, 9), 0, 1000.0, 10.0);
+        ant.food = 10.0;
+        ant.returning_to_queen = true;
+        let queen = Queen { position: (0, 0) };
+        let mut colony = Colony::new(config.clone(), 0, queen, vec![ant], 0).unwrap();
+
+        let mut path = vec![(9, 9)];
+        while colony.food_store == 0.0 {
+            assert!(path.len() < 40, "never got home: {path:?}");
+            colony.update_ants(&mut environment, 0.1);
+            path.push(colony.ants.positions[0]);
+        }
+        assert_eq!(path.last(), Some(&(0, 0)));
+        for step in path.windows(2) {
+            let ((x0, y0), (x1, y1)) = (step[0], step[1]);
+            assert!(
+                terrain.is_open((x0, y1)) || terrain.is_open((x1, y0)),
+                "cut the corner from {:?} to {:?}",
+                step[0],
+                step[1]
+            );
+        }
+    }
+}
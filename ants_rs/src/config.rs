@@ -0,0 +1,1364 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyDict, PyList, PyTuple},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "python")]
+use serde_json::Value;
+
+use crate::ant_store::MAX_ANTS;
+use crate::config_error::ConfigError;
+#[cfg(feature = "python")]
+use crate::settings;
+
+// Ant ids set aside 8 bits for the colony; see `ant_store.rs`.
+pub const MAX_COLONIES: usize = 256;
+
+// How the Rust environment applies pheromone evaporation. `Eager` decays the
+// whole grid every step like the Python version; `Lazy` stamps each cell when
+// it is written and replays the steps it missed when it is read. Both give
+// bit-identical levels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EvaporationMode {
+    #[default]
+    Eager,
+    Lazy,
+}
+
+impl EvaporationMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvaporationMode::Eager => "eager",
+            EvaporationMode::Lazy => "lazy",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for EvaporationMode {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "eager" => Ok(EvaporationMode::Eager),
+            "lazy" => Ok(EvaporationMode::Lazy),
+            other => Err(PyValueError::new_err(format!(
+                "unknown pheromone_evaporation_mode {other:?}, expected 'eager' or 'lazy'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for EvaporationMode {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// How returning ants find the queen. `Greedy` steps straight towards her like
+// `Ant.move_towards`, which gets stuck behind walls; `Field` follows a
+// distance field from the nest around them. `Auto` uses the field only when
+// the terrain has walls, so open grids keep the Python behaviour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HomingMode {
+    #[default]
+    Auto,
+    Greedy,
+    Field,
+}
+
+impl HomingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HomingMode::Auto => "auto",
+            HomingMode::Greedy => "greedy",
+            HomingMode::Field => "field",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for HomingMode {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "auto" => Ok(HomingMode::Auto),
+            "greedy" => Ok(HomingMode::Greedy),
+            "field" => Ok(HomingMode::Field),
+            other => Err(PyValueError::new_err(format!(
+                "unknown homing_mode {other:?}, expected 'auto', 'greedy' or 'field'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for HomingMode {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// What lies past the edge of the grid. `Torus` wraps around like the Python
+// version; `Bounded` edges are walls; `Reflecting` edges bounce a step back
+// into the grid. Distances and perception only wrap on the torus; food
+// spawns uniformly over the grid in every mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoundaryMode {
+    #[default]
+    Torus,
+    Bounded,
+    Reflecting,
+}
+
+impl BoundaryMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoundaryMode::Torus => "torus",
+            BoundaryMode::Bounded => "bounded",
+            BoundaryMode::Reflecting => "reflecting",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for BoundaryMode {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "torus" => Ok(BoundaryMode::Torus),
+            "bounded" => Ok(BoundaryMode::Bounded),
+            "reflecting" => Ok(BoundaryMode::Reflecting),
+            other => Err(PyValueError::new_err(format!(
+                "unknown boundary_mode {other:?}, expected 'torus', 'bounded' or 'reflecting'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for BoundaryMode {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// Which cells are adjacent: the 4 straight neighbours of the Python
+// version, all 8 (Moore, with Chebyshev distance) or the 6 of a hexagonal
+// grid. Movement, perception radii and homing all follow it; see
+// `neighborhood.rs` for the hex layout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighborhood {
+    #[default]
+    VonNeumann,
+    Moore,
+    Hex,
+}
+
+impl Neighborhood {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Neighborhood::VonNeumann => "von_neumann",
+            Neighborhood::Moore => "moore",
+            Neighborhood::Hex => "hex",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Neighborhood {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "von_neumann" => Ok(Neighborhood::VonNeumann),
+            "moore" => Ok(Neighborhood::Moore),
+            "hex" => Ok(Neighborhood::Hex),
+            other => Err(PyValueError::new_err(format!(
+                "unknown neighborhood {other:?}, expected 'von_neumann', 'moore' or 'hex'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for Neighborhood {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// Who may forage where with several colonies. `Shared` food is first come,
+// first served; under `Exclusive`, the first colony to take from a source
+// claims it, and rival ants neither see nor take from it until it runs out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FoodSharing {
+    #[default]
+    Shared,
+    Exclusive,
+}
+
+impl FoodSharing {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FoodSharing::Shared => "shared",
+            FoodSharing::Exclusive => "exclusive",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for FoodSharing {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "shared" => Ok(FoodSharing::Shared),
+            "exclusive" => Ok(FoodSharing::Exclusive),
+            other => Err(PyValueError::new_err(format!(
+                "unknown food_sharing {other:?}, expected 'shared' or 'exclusive'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for FoodSharing {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// What ants of rival colonies do when they meet. `Ignore` lets them pass
+// through each other; `Avoid` keeps wandering ants off cells rivals stood on
+// at the start of the step unless there is nowhere else to go; under `Steal`,
+// a forager sharing a cell with a laden rival takes what it can carry; under
+// `Fight`, rivals on the same or neighbouring cells fight by the `combat`
+// rules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncounterMode {
+    #[default]
+    Ignore,
+    Avoid,
+    Steal,
+    Fight,
+}
+
+impl EncounterMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EncounterMode::Ignore => "ignore",
+            EncounterMode::Avoid => "avoid",
+            EncounterMode::Steal => "steal",
+            EncounterMode::Fight => "fight",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for EncounterMode {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "ignore" => Ok(EncounterMode::Ignore),
+            "avoid" => Ok(EncounterMode::Avoid),
+            "steal" => Ok(EncounterMode::Steal),
+            "fight" => Ok(EncounterMode::Fight),
+            other => Err(PyValueError::new_err(format!(
+                "unknown encounter_mode {other:?}, expected 'ignore', 'avoid', 'steal' or 'fight'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for EncounterMode {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// Mirrors `config.AnimationConfig`.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimationConfig {
+    pub figsize: (u32, u32),
+    pub step_interval: f64,
+    pub fps: u32,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        AnimationConfig {
+            figsize: (8, 6),
+            step_interval: 0.1,
+            fps: 30,
+        }
+    }
+}
+
+// Mirrors `config.FoodAllocationConfig`.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FoodAllocationConfig {
+    pub spawn_chance: f64,
+    pub spawn_baseline: i64,
+    pub spawn_variance: i64,
+    pub value_baseline: f64,
+    pub value_variance: f64,
+}
+
+impl Default for FoodAllocationConfig {
+    fn default() -> Self {
+        FoodAllocationConfig {
+            spawn_chance: 0.1,
+            spawn_baseline: 3,
+            spawn_variance: 2,
+            value_baseline: 5.0,
+            value_variance: 3.0,
+        }
+    }
+}
+
+// Mirrors `config.AntConfig`.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AntConfig {
+    pub carrying_capacity: f64,
+    pub initial_lifespan: f64,
+    pub lifespan_extension_on_contribution: f64,
+}
+
+impl Default for AntConfig {
+    fn default() -> Self {
+        AntConfig {
+            carrying_capacity: 10.0,
+            initial_lifespan: 1000.0,
+            lifespan_extension_on_contribution: 20.0,
+        }
+    }
+}
+
+// Mirrors `config.CombatConfig`; only used when `encounter_mode` is "fight".
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CombatConfig {
+    // Fighting strength of each colony's ants, by colony index; colonies past
+    // the end of the list have strength 1. An ant beats a rival with
+    // probability `own / (own + rival)`.
+    pub strength: Vec<f64>,
+    // Chance per step that an ant picks a fight with a rival in reach
+    pub aggression: f64,
+    pub loser_death_chance: f64,
+    pub winner_death_chance: f64,
+}
+
+impl Default for CombatConfig {
+    fn default() -> Self {
+        CombatConfig {
+            strength: Vec::new(),
+            aggression: 0.5,
+            loser_death_chance: 1.0,
+            winner_death_chance: 0.0,
+        }
+    }
+}
+
+impl CombatConfig {
+    pub fn strength_of(&self, colony: usize) -> f64 {
+        self.strength.get(colony).copied().unwrap_or(1.0)
+    }
+}
+
+// How a forager back from a source that still has food calls nestmates to
+// it. `Tandem` leads a single recruit there, which follows one step behind;
+// under `Mass`, the recruits make their own way to the source.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecruitmentMode {
+    #[default]
+    None,
+    Tandem,
+    Mass,
+}
+
+impl RecruitmentMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecruitmentMode::None => "none",
+            RecruitmentMode::Tandem => "tandem",
+            RecruitmentMode::Mass => "mass",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for RecruitmentMode {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "none" => Ok(RecruitmentMode::None),
+            "tandem" => Ok(RecruitmentMode::Tandem),
+            "mass" => Ok(RecruitmentMode::Mass),
+            other => Err(PyValueError::new_err(format!(
+                "unknown recruitment mode {other:?}, expected 'none', 'tandem' or 'mass'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for RecruitmentMode {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// Mirrors `config.RecruitmentConfig`.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecruitmentConfig {
+    pub mode: RecruitmentMode,
+    // Recruits per unit of food left at the source, rounded up; for a tandem
+    // run, the chance of finding a follower
+    pub recruits_per_food: f64,
+    pub max_recruits: usize,
+    // How close to the queen an idle forager has to be to be recruited
+    pub nest_radius: usize,
+}
+
+impl Default for RecruitmentConfig {
+    fn default() -> Self {
+        RecruitmentConfig {
+            mode: RecruitmentMode::None,
+            recruits_per_food: 0.2,
+            max_recruits: 10,
+            nest_radius: 3,
+        }
+    }
+}
+
+// Mirrors `config.EnergyConfig`. While `enabled`, every ant has an energy
+// budget of up to `capacity`, spent on each move and at `metabolic_rate` per
+// unit of time. Below `hunger_threshold` of capacity an ant eats, first from
+// what it carries and then from the colony's store if it is at the nest,
+// each unit of food giving `food_energy`; ants with nothing to eat head home,
+// and those that run out starve. The colony store also feeds the queen and
+// brood at `colony_consumption_rate` per ant per unit of time.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnergyConfig {
+    pub enabled: bool,
+    pub capacity: f64,
+    pub cost_per_move: f64,
+    pub metabolic_rate: f64,
+    pub hunger_threshold: f64,
+    pub food_energy: f64,
+    pub colony_consumption_rate: f64,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        EnergyConfig {
+            enabled: false,
+            capacity: 100.0,
+            cost_per_move: 1.0,
+            metabolic_rate: 0.0,
+            hunger_threshold: 0.5,
+            food_energy: 10.0,
+            colony_consumption_rate: 0.0,
+        }
+    }
+}
+
+// An ant's role in its colony, picked when it hatches by the
+// `castes.demand` rules. Foragers, scouts and soldiers all forage, each with
+// their own `CasteConfig`; nurses stay with the queen and speed up the brood.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Caste {
+    #[default]
+    Forager,
+    Scout,
+    Soldier,
+    Nurse,
+}
+
+impl Caste {
+    pub const ALL: [Caste; 4] = [Caste::Forager, Caste::Scout, Caste::Soldier, Caste::Nurse];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Caste::Forager => "forager",
+            Caste::Scout => "scout",
+            Caste::Soldier => "soldier",
+            Caste::Nurse => "nurse",
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Caste {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match obj.extract::<&str>()? {
+            "forager" => Ok(Caste::Forager),
+            "scout" => Ok(Caste::Scout),
+            "soldier" => Ok(Caste::Soldier),
+            "nurse" => Ok(Caste::Nurse),
+            other => Err(PyValueError::new_err(format!(
+                "unknown caste {other:?}, expected 'forager', 'scout', 'soldier' or 'nurse'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl IntoPy<PyObject> for Caste {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        self.as_str().into_py(py)
+    }
+}
+
+// Mirrors `config.CasteConfig`. Unset fields fall back to `ant` and the
+// top-level settings, so by default every caste behaves like a plain ant.
+// Nurses never leave the nest, so only their lifespan and strength matter.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CasteConfig {
+    pub carrying_capacity: Option<f64>,
+    pub initial_lifespan: Option<f64>,
+    // Moves per step
+    pub speed: usize,
+    pub perception_radius: Option<usize>,
+    pub randomness_factor: Option<f64>,
+    // Multiplies the colony's `combat.strength`
+    pub strength: f64,
+}
+
+impl Default for CasteConfig {
+    fn default() -> Self {
+        CasteConfig {
+            carrying_capacity: None,
+            initial_lifespan: None,
+            speed: 1,
+            perception_radius: None,
+            randomness_factor: None,
+            strength: 1.0,
+        }
+    }
+}
+
+// Mirrors `config.CasteDemand`. A hatchling joins the caste furthest below
+// its target head count, the first in `Caste::ALL` order on ties. Targets
+// split the colony by the weights below, and the nurse and soldier targets
+// are raised to `nurses_per_egg` per egg in the brood and
+// `soldiers_per_casualty` per ant lost in fights.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CasteDemand {
+    pub forager: f64,
+    pub scout: f64,
+    pub soldier: f64,
+    pub nurse: f64,
+    pub nurses_per_egg: f64,
+    pub soldiers_per_casualty: f64,
+}
+
+impl Default for CasteDemand {
+    fn default() -> Self {
+        CasteDemand {
+            forager: 1.0,
+            scout: 0.0,
+            soldier: 0.0,
+            nurse: 0.0,
+            nurses_per_egg: 0.0,
+            soldiers_per_casualty: 0.0,
+        }
+    }
+}
+
+impl CasteDemand {
+    pub fn weight(&self, caste: Caste) -> f64 {
+        match caste {
+            Caste::Forager => self.forager,
+            Caste::Scout => self.scout,
+            Caste::Soldier => self.soldier,
+            Caste::Nurse => self.nurse,
+        }
+    }
+}
+
+// Mirrors `config.CastesConfig`.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CastesConfig {
+    pub forager: CasteConfig,
+    pub scout: CasteConfig,
+    pub soldier: CasteConfig,
+    pub nurse: CasteConfig,
+    pub demand: CasteDemand,
+    // Extra egg development speed, as a fraction, with at least one nurse
+    // per egg; fewer nurses help proportionally less
+    pub brood_care: f64,
+}
+
+impl Default for CastesConfig {
+    fn default() -> Self {
+        CastesConfig {
+            forager: CasteConfig::default(),
+            scout: CasteConfig::default(),
+            soldier: CasteConfig::default(),
+            nurse: CasteConfig::default(),
+            demand: CasteDemand::default(),
+            brood_care: 0.5,
+        }
+    }
+}
+
+impl CastesConfig {
+    pub fn get(&self, caste: Caste) -> &CasteConfig {
+        match caste {
+            Caste::Forager => &self.forager,
+            Caste::Scout => &self.scout,
+            Caste::Soldier => &self.soldier,
+            Caste::Nurse => &self.nurse,
+        }
+    }
+}
+
+// Mirrors `config.SimulationConfig`, defaults included.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub grid_size: (usize, usize),
+    pub num_ants: usize,
+    pub simulation_duration: f64,
+    pub food: FoodAllocationConfig,
+    pub ant: AntConfig,
+    pub food_required_to_lay_egg: f64,
+    pub egg_gestation_period: f64,
+    pub pheromone_initial_intensity: f64,
+    pub pheromone_evaporation_rate: f64,
+    pub pheromone_evaporation_mode: EvaporationMode,
+    pub pheromone_max_opacity: f64,
+    pub randomness_factor: f64,
+    pub enable_multiple_pheromones: bool,
+    // PNG or ASCII map of impassable cells, stretched over the grid; see
+    // `Terrain::load`.
+    pub terrain_map: Option<PathBuf>,
+    pub homing_mode: HomingMode,
+    pub boundary_mode: BoundaryMode,
+    pub neighborhood: Neighborhood,
+    // Colonies sharing the grid, each with its own queen, `num_ants` initial
+    // ants and pheromone layers
+    pub num_colonies: usize,
+    pub food_sharing: FoodSharing,
+    pub encounter_mode: EncounterMode,
+    pub combat: CombatConfig,
+    pub castes: CastesConfig,
+    pub recruitment: RecruitmentConfig,
+    pub energy: EnergyConfig,
+    pub animation: AnimationConfig,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            grid_size: (200, 200),
+            num_ants: 100,
+            simulation_duration: 60.0,
+            food: FoodAllocationConfig::default(),
+            ant: AntConfig::default(),
+            food_required_to_lay_egg: 42.0,
+            egg_gestation_period: 10.0,
+            pheromone_initial_intensity: 1.0,
+            pheromone_evaporation_rate: 0.2,
+            pheromone_evaporation_mode: EvaporationMode::Eager,
+            pheromone_max_opacity: 0.5,
+            randomness_factor: 0.3,
+            enable_multiple_pheromones: true,
+            terrain_map: None,
+            homing_mode: HomingMode::Auto,
+            boundary_mode: BoundaryMode::Torus,
+            neighborhood: Neighborhood::VonNeumann,
+            num_colonies: 1,
+            food_sharing: FoodSharing::Shared,
+            encounter_mode: EncounterMode::Ignore,
+            combat: CombatConfig::default(),
+            castes: CastesConfig::default(),
+            recruitment: RecruitmentConfig::default(),
+            energy: EnergyConfig::default(),
+            animation: AnimationConfig::default(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    // Same formula as the `perception_radius` computed field in Python.
+    pub fn perception_radius(&self) -> usize {
+        let avg_dimension = (self.grid_size.0 + self.grid_size.1) as f64 / 2.0;
+        2.max((avg_dimension / 5.0) as usize)
+    }
+
+    // Caste settings with their fallbacks applied.
+    pub fn carrying_capacity_of(&self, caste: Caste) -> f64 {
+        let caste = self.castes.get(caste);
+        caste
+            .carrying_capacity
+            .unwrap_or(self.ant.carrying_capacity)
+    }
+
+    pub fn initial_lifespan_of(&self, caste: Caste) -> f64 {
+        let caste = self.castes.get(caste);
+        caste.initial_lifespan.unwrap_or(self.ant.initial_lifespan)
+    }
+
+    pub fn perception_radius_of(&self, caste: Caste) -> usize {
+        let caste = self.castes.get(caste);
+        caste
+            .perception_radius
+            .unwrap_or_else(|| self.perception_radius())
+    }
+
+    pub fn randomness_factor_of(&self, caste: Caste) -> f64 {
+        let caste = self.castes.get(caste);
+        caste.randomness_factor.unwrap_or(self.randomness_factor)
+    }
+
+    // The `PositiveInt`/`PositiveFloat` constraints from `config.py`, plus
+    // the combinations pydantic doesn't check. NaN fails every check.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.grid_size.0 == 0 || self.grid_size.1 == 0 {
+            return Err(ConfigError::EmptyGrid {
+                grid_size: self.grid_size,
+            });
+        }
+
+        let positive: [(&'static str, f64); 20] = [
+            ("num_ants", self.num_ants as f64),
+            ("num_colonies", self.num_colonies as f64),
+            ("simulation_duration", self.simulation_duration),
+            ("food.spawn_chance", self.food.spawn_chance),
+            ("food.spawn_baseline", self.food.spawn_baseline as f64),
+            ("food.spawn_variance", self.food.spawn_variance as f64),
+            ("food.value_baseline", self.food.value_baseline),
+            ("food.value_variance", self.food.value_variance),
+            ("ant.carrying_capacity", self.ant.carrying_capacity),
+            ("ant.initial_lifespan", self.ant.initial_lifespan),
+            (
+                "ant.lifespan_extension_on_contribution",
+                self.ant.lifespan_extension_on_contribution,
+            ),
+            ("food_required_to_lay_egg", self.food_required_to_lay_egg),
+            ("egg_gestation_period", self.egg_gestation_period),
+            (
+                "pheromone_initial_intensity",
+                self.pheromone_initial_intensity,
+            ),
+            (
+                "pheromone_evaporation_rate",
+                self.pheromone_evaporation_rate,
+            ),
+            ("pheromone_max_opacity", self.pheromone_max_opacity),
+            ("energy.capacity", self.energy.capacity),
+            ("energy.food_energy", self.energy.food_energy),
+            ("animation.step_interval", self.animation.step_interval),
+            ("animation.fps", self.animation.fps as f64),
+        ];
+        for (field, value) in positive {
+            if value.is_nan() || value <= 0.0 {
+                return Err(ConfigError::NotPositive { field, value });
+            }
+        }
+
+        if !self.pheromone_evaporation_rate.is_finite() {
+            return Err(ConfigError::NotFinite {
+                field: "pheromone_evaporation_rate",
+                value: self.pheromone_evaporation_rate,
+            });
+        }
+
+        if self.num_ants > MAX_ANTS {
+            return Err(ConfigError::OutOfRange {
+                field: "num_ants",
+                value: self.num_ants as f64,
+                min: 1.0,
+                max: MAX_ANTS as f64,
+            });
+        }
+
+        if self.num_colonies > MAX_COLONIES {
+            return Err(ConfigError::OutOfRange {
+                field: "num_colonies",
+                value: self.num_colonies as f64,
+                min: 1.0,
+                max: MAX_COLONIES as f64,
+            });
+        }
+
+        let probabilities: [(&'static str, f64); 5] = [
+            ("randomness_factor", self.randomness_factor),
+            ("energy.hunger_threshold", self.energy.hunger_threshold),
+            ("combat.aggression", self.combat.aggression),
+            ("combat.loser_death_chance", self.combat.loser_death_chance),
+            (
+                "combat.winner_death_chance",
+                self.combat.winner_death_chance,
+            ),
+        ];
+        for (field, value) in probabilities {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ConfigError::OutOfRange {
+                    field,
+                    value,
+                    min: 0.0,
+                    max: 1.0,
+                });
+            }
+        }
+        if let Some(&value) = self
+            .combat
+            .strength
+            .iter()
+            .find(|&&strength| strength.is_nan() || strength <= 0.0)
+        {
+            return Err(ConfigError::NotPositive {
+                field: "combat.strength",
+                value,
+            });
+        }
+
+        for caste in Caste::ALL {
+            let config = self.castes.get(caste);
+            let [capacity, lifespan, randomness, strength, speed] = caste_fields(caste);
+            let positive = [
+                (capacity, config.carrying_capacity),
+                (lifespan, config.initial_lifespan),
+                (strength, Some(config.strength)),
+                (speed, Some(config.speed as f64)),
+            ];
+            for (field, value) in positive {
+                if let Some(value) = value.filter(|value| value.is_nan() || *value <= 0.0) {
+                    return Err(ConfigError::NotPositive { field, value });
+                }
+            }
+            if let Some(value) = config
+                .randomness_factor
+                .filter(|value| !(0.0..=1.0).contains(value))
+            {
+                return Err(ConfigError::OutOfRange {
+                    field: randomness,
+                    value,
+                    min: 0.0,
+                    max: 1.0,
+                });
+            }
+        }
+        let demand = &self.castes.demand;
+        let non_negative: [(&'static str, f64); 11] = [
+            ("energy.cost_per_move", self.energy.cost_per_move),
+            ("energy.metabolic_rate", self.energy.metabolic_rate),
+            (
+                "energy.colony_consumption_rate",
+                self.energy.colony_consumption_rate,
+            ),
+            (
+                "recruitment.recruits_per_food",
+                self.recruitment.recruits_per_food,
+            ),
+            ("castes.demand.forager", demand.forager),
+            ("castes.demand.scout", demand.scout),
+            ("castes.demand.soldier", demand.soldier),
+            ("castes.demand.nurse", demand.nurse),
+            ("castes.demand.nurses_per_egg", demand.nurses_per_egg),
+            (
+                "castes.demand.soldiers_per_casualty",
+                demand.soldiers_per_casualty,
+            ),
+            ("castes.brood_care", self.castes.brood_care),
+        ];
+        for (field, value) in non_negative {
+            if !(0.0..f64::INFINITY).contains(&value) {
+                return Err(ConfigError::OutOfRange {
+                    field,
+                    value,
+                    min: 0.0,
+                    max: f64::INFINITY,
+                });
+            }
+        }
+
+        // `random.randint(baseline - variance, ...)` and
+        // `random.uniform(baseline - variance, ...)` in `spawn_food`
+        if self.food.spawn_variance > self.food.spawn_baseline {
+            return Err(ConfigError::VarianceExceedsBaseline {
+                field: "food.spawn_variance",
+                baseline_field: "food.spawn_baseline",
+                variance: self.food.spawn_variance as f64,
+                baseline: self.food.spawn_baseline as f64,
+            });
+        }
+        if self.food.value_variance > self.food.value_baseline {
+            return Err(ConfigError::VarianceExceedsBaseline {
+                field: "food.value_variance",
+                baseline_field: "food.value_baseline",
+                variance: self.food.value_variance,
+                baseline: self.food.value_baseline,
+            });
+        }
+        Ok(())
+    }
+
+    // Builds a config from a pydantic `SimulationConfig`, a plain dict, or an
+    // `ants_rs.SimulationConfig`. Missing keys fall back to the defaults, and
+    // the result is validated.
+    #[cfg(feature = "python")]
+    pub fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let config = SimulationConfig::from_py_unchecked(obj)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[cfg(feature = "python")]
+    fn from_py_unchecked(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<SimulationConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = SimulationConfig::default();
+        Ok(SimulationConfig {
+            grid_size: field(obj, "grid_size", d.grid_size)?,
+            num_ants: field(obj, "num_ants", d.num_ants)?,
+            simulation_duration: field(obj, "simulation_duration", d.simulation_duration)?,
+            food: match lookup(obj, "food")? {
+                Some(food) => FoodAllocationConfig::from_py(&food)?,
+                None => d.food,
+            },
+            ant: match lookup(obj, "ant")? {
+                Some(ant) => AntConfig::from_py(&ant)?,
+                None => d.ant,
+            },
+            food_required_to_lay_egg: field(
+                obj,
+                "food_required_to_lay_egg",
+                d.food_required_to_lay_egg,
+            )?,
+            egg_gestation_period: field(obj, "egg_gestation_period", d.egg_gestation_period)?,
+            pheromone_initial_intensity: field(
+                obj,
+                "pheromone_initial_intensity",
+                d.pheromone_initial_intensity,
+            )?,
+            pheromone_evaporation_rate: field(
+                obj,
+                "pheromone_evaporation_rate",
+                d.pheromone_evaporation_rate,
+            )?,
+            pheromone_evaporation_mode: field(
+                obj,
+                "pheromone_evaporation_mode",
+                d.pheromone_evaporation_mode,
+            )?,
+            pheromone_max_opacity: field(obj, "pheromone_max_opacity", d.pheromone_max_opacity)?,
+            randomness_factor: field(obj, "randomness_factor", d.randomness_factor)?,
+            enable_multiple_pheromones: field(
+                obj,
+                "enable_multiple_pheromones",
+                d.enable_multiple_pheromones,
+            )?,
+            terrain_map: field(obj, "terrain_map", d.terrain_map)?,
+            homing_mode: field(obj, "homing_mode", d.homing_mode)?,
+            boundary_mode: field(obj, "boundary_mode", d.boundary_mode)?,
+            neighborhood: field(obj, "neighborhood", d.neighborhood)?,
+            num_colonies: field(obj, "num_colonies", d.num_colonies)?,
+            food_sharing: field(obj, "food_sharing", d.food_sharing)?,
+            encounter_mode: field(obj, "encounter_mode", d.encounter_mode)?,
+            combat: match lookup(obj, "combat")? {
+                Some(combat) => CombatConfig::from_py(&combat)?,
+                None => d.combat,
+            },
+            castes: match lookup(obj, "castes")? {
+                Some(castes) => CastesConfig::from_py(&castes)?,
+                None => d.castes,
+            },
+            recruitment: match lookup(obj, "recruitment")? {
+                Some(recruitment) => RecruitmentConfig::from_py(&recruitment)?,
+                None => d.recruitment,
+            },
+            energy: match lookup(obj, "energy")? {
+                Some(energy) => EnergyConfig::from_py(&energy)?,
+                None => d.energy,
+            },
+            animation: match lookup(obj, "animation")? {
+                Some(animation) => AnimationConfig::from_py(&animation)?,
+                None => d.animation,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl FoodAllocationConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<FoodAllocationConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = FoodAllocationConfig::default();
+        Ok(FoodAllocationConfig {
+            spawn_chance: field(obj, "spawn_chance", d.spawn_chance)?,
+            spawn_baseline: field(obj, "spawn_baseline", d.spawn_baseline)?,
+            spawn_variance: field(obj, "spawn_variance", d.spawn_variance)?,
+            value_baseline: field(obj, "value_baseline", d.value_baseline)?,
+            value_variance: field(obj, "value_variance", d.value_variance)?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl AntConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<AntConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = AntConfig::default();
+        Ok(AntConfig {
+            carrying_capacity: field(obj, "carrying_capacity", d.carrying_capacity)?,
+            initial_lifespan: field(obj, "initial_lifespan", d.initial_lifespan)?,
+            lifespan_extension_on_contribution: field(
+                obj,
+                "lifespan_extension_on_contribution",
+                d.lifespan_extension_on_contribution,
+            )?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl CombatConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<CombatConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = CombatConfig::default();
+        Ok(CombatConfig {
+            strength: field(obj, "strength", d.strength)?,
+            aggression: field(obj, "aggression", d.aggression)?,
+            loser_death_chance: field(obj, "loser_death_chance", d.loser_death_chance)?,
+            winner_death_chance: field(obj, "winner_death_chance", d.winner_death_chance)?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl CasteConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<CasteConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = CasteConfig::default();
+        Ok(CasteConfig {
+            carrying_capacity: field(obj, "carrying_capacity", d.carrying_capacity)?,
+            initial_lifespan: field(obj, "initial_lifespan", d.initial_lifespan)?,
+            speed: field(obj, "speed", d.speed)?,
+            perception_radius: field(obj, "perception_radius", d.perception_radius)?,
+            randomness_factor: field(obj, "randomness_factor", d.randomness_factor)?,
+            strength: field(obj, "strength", d.strength)?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl CasteDemand {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<CasteDemand>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = CasteDemand::default();
+        Ok(CasteDemand {
+            forager: field(obj, "forager", d.forager)?,
+            scout: field(obj, "scout", d.scout)?,
+            soldier: field(obj, "soldier", d.soldier)?,
+            nurse: field(obj, "nurse", d.nurse)?,
+            nurses_per_egg: field(obj, "nurses_per_egg", d.nurses_per_egg)?,
+            soldiers_per_casualty: field(obj, "soldiers_per_casualty", d.soldiers_per_casualty)?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl CastesConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<CastesConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = CastesConfig::default();
+        let caste = |name: &str, default: CasteConfig| -> PyResult<CasteConfig> {
+            match lookup(obj, name)? {
+                Some(caste) => CasteConfig::from_py(&caste),
+                None => Ok(default),
+            }
+        };
+        Ok(CastesConfig {
+            forager: caste("forager", d.forager)?,
+            scout: caste("scout", d.scout)?,
+            soldier: caste("soldier", d.soldier)?,
+            nurse: caste("nurse", d.nurse)?,
+            demand: match lookup(obj, "demand")? {
+                Some(demand) => CasteDemand::from_py(&demand)?,
+                None => d.demand,
+            },
+            brood_care: field(obj, "brood_care", d.brood_care)?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl RecruitmentConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<RecruitmentConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = RecruitmentConfig::default();
+        Ok(RecruitmentConfig {
+            mode: field(obj, "mode", d.mode)?,
+            recruits_per_food: field(obj, "recruits_per_food", d.recruits_per_food)?,
+            max_recruits: field(obj, "max_recruits", d.max_recruits)?,
+            nest_radius: field(obj, "nest_radius", d.nest_radius)?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl EnergyConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<EnergyConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = EnergyConfig::default();
+        Ok(EnergyConfig {
+            enabled: field(obj, "enabled", d.enabled)?,
+            capacity: field(obj, "capacity", d.capacity)?,
+            cost_per_move: field(obj, "cost_per_move", d.cost_per_move)?,
+            metabolic_rate: field(obj, "metabolic_rate", d.metabolic_rate)?,
+            hunger_threshold: field(obj, "hunger_threshold", d.hunger_threshold)?,
+            food_energy: field(obj, "food_energy", d.food_energy)?,
+            colony_consumption_rate: field(
+                obj,
+                "colony_consumption_rate",
+                d.colony_consumption_rate,
+            )?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl AnimationConfig {
+    fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(config) = obj.downcast::<AnimationConfig>() {
+            return Ok(config.borrow().clone());
+        }
+        let d = AnimationConfig::default();
+        Ok(AnimationConfig {
+            figsize: field(obj, "figsize", d.figsize)?,
+            step_interval: field(obj, "step_interval", d.step_interval)?,
+            fps: field(obj, "fps", d.fps)?,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SimulationConfig {
+    #[new]
+    #[pyo3(signature = (config=None))]
+    fn py_new(config: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        match config {
+            Some(config) => SimulationConfig::from_py(config),
+            None => Ok(SimulationConfig::default()),
+        }
+    }
+
+    // Mirrors building `config.SimulationConfig()` with pydantic-settings,
+    // but starting from a TOML, YAML or JSON file when `path` is given.
+    #[staticmethod]
+    #[pyo3(signature = (path=None))]
+    fn load(path: Option<PathBuf>) -> PyResult<Self> {
+        Ok(settings::load(path.as_deref())?)
+    }
+
+    // Raises a subclass of `ants_rs.ConfigError` naming the first bad field;
+    // useful after changing fields through the setters.
+    #[pyo3(name = "validate")]
+    fn py_validate(&self) -> PyResult<()> {
+        Ok(self.validate()?)
+    }
+
+    #[getter(perception_radius)]
+    fn get_perception_radius(&self) -> usize {
+        self.perception_radius()
+    }
+
+    // Same shape as `model_dump()` of the pydantic model, so
+    // `config.SimulationConfig(**c.to_dict())` rebuilds it.
+    fn to_dict(&self, py: Python<'_>) -> PyObject {
+        value_to_py(py, &settings::to_value(self))
+    }
+}
+
+// Dotted paths of the checked fields in a caste's block: carrying_capacity,
+// initial_lifespan, randomness_factor and strength.
+fn caste_fields(caste: Caste) -> [&'static str; 5] {
+    match caste {
+        Caste::Forager => [
+            "castes.forager.carrying_capacity",
+            "castes.forager.initial_lifespan",
+            "castes.forager.randomness_factor",
+            "castes.forager.strength",
+            "castes.forager.speed",
+        ],
+        Caste::Scout => [
+            "castes.scout.carrying_capacity",
+            "castes.scout.initial_lifespan",
+            "castes.scout.randomness_factor",
+            "castes.scout.strength",
+            "castes.scout.speed",
+        ],
+        Caste::Soldier => [
+            "castes.soldier.carrying_capacity",
+            "castes.soldier.initial_lifespan",
+            "castes.soldier.randomness_factor",
+            "castes.soldier.strength",
+            "castes.soldier.speed",
+        ],
+        Caste::Nurse => [
+            "castes.nurse.carrying_capacity",
+            "castes.nurse.initial_lifespan",
+            "castes.nurse.randomness_factor",
+            "castes.nurse.strength",
+            "castes.nurse.speed",
+        ],
+    }
+}
+
+// Looks up `name` as a dict key or an attribute, whichever `obj` supports.
+#[cfg(feature = "python")]
+fn lookup<'py>(obj: &Bound<'py, PyAny>, name: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        return dict.get_item(name);
+    }
+    if obj.hasattr(name)? {
+        return Ok(Some(obj.getattr(name)?));
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "python")]
+fn field<'py, T: FromPyObject<'py>>(
+    obj: &Bound<'py, PyAny>,
+    name: &str,
+    default: T,
+) -> PyResult<T> {
+    match lookup(obj, name)? {
+        Some(value) => value.extract(),
+        None => Ok(default),
+    }
+}
+
+// Config fields that `model_dump()` gives as lists rather than tuples.
+#[cfg(feature = "python")]
+const LIST_FIELDS: [&str; 1] = ["strength"];
+
+// JSON to Python. Apart from `LIST_FIELDS`, the arrays in a config are
+// fixed-size tuples like `grid_size`, so arrays become tuples, as in
+// `model_dump()`.
+#[cfg(feature = "python")]
+fn value_to_py(py: Python<'_>, value: &Value) -> PyObject {
+    match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into_py(py),
+            None => n.as_f64().into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            PyTuple::new_bound(py, items.iter().map(|item| value_to_py(py, item))).into_py(py)
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new_bound(py);
+            for (key, item) in fields {
+                let item = match item {
+                    Value::Array(items) if LIST_FIELDS.contains(&key.as_str()) => {
+                        PyList::new_bound(py, items.iter().map(|item| value_to_py(py, item)))
+                            .into_py(py)
+                    }
+                    _ => value_to_py(py, item),
+                };
+                dict.set_item(key, item)
+                    .expect("setting a str key cannot fail");
+            }
+            dict.into_py(py)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The error `validate` gives for the default config changed by `change`.
+    fn invalid(change: impl FnOnce(&mut SimulationConfig)) -> ConfigError {
+        let mut config = SimulationConfig::default();
+        change(&mut config);
+        config.validate().unwrap_err()
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        SimulationConfig::default().validate().unwrap();
+    }
+
+    #[test]
+    fn each_error_names_its_field() {
+        let error = invalid(|config| config.grid_size = (0, 10));
+        assert!(matches!(error, ConfigError::EmptyGrid { .. }), "{error}");
+        assert_eq!(error.field(), Some("grid_size"));
+
+        let error = invalid(|config| config.food.spawn_chance = 0.0);
+        assert!(matches!(error, ConfigError::NotPositive { .. }), "{error}");
+        assert_eq!(error.field(), Some("food.spawn_chance"));
+
+        let error = invalid(|config| config.castes.soldier.speed = 0);
+        assert!(matches!(error, ConfigError::NotPositive { .. }), "{error}");
+        assert_eq!(error.field(), Some("castes.soldier.speed"));
+
+        let error = invalid(|config| config.pheromone_evaporation_rate = f64::INFINITY);
+        assert!(matches!(error, ConfigError::NotFinite { .. }), "{error}");
+        assert_eq!(error.field(), Some("pheromone_evaporation_rate"));
+
+        let error = invalid(|config| config.combat.aggression = 1.5);
+        assert!(matches!(error, ConfigError::OutOfRange { .. }), "{error}");
+        assert_eq!(error.field(), Some("combat.aggression"));
+
+        let error = invalid(|config| config.num_colonies = MAX_COLONIES + 1);
+        assert!(matches!(error, ConfigError::OutOfRange { .. }), "{error}");
+        assert_eq!(error.field(), Some("num_colonies"));
+
+        let error = invalid(|config| config.food.value_variance = 100.0);
+        assert!(
+            matches!(error, ConfigError::VarianceExceedsBaseline { .. }),
+            "{error}"
+        );
+        assert_eq!(error.field(), Some("food.value_variance"));
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::{exceptions::PyIOError, prelude::*};
+
+// Everything that can go wrong building a `SimulationConfig`. The validation
+// variants carry the dotted path of the field at fault, e.g.
+// `food.spawn_variance`.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+    Env {
+        var: String,
+        message: String,
+    },
+    EmptyGrid {
+        grid_size: (usize, usize),
+    },
+    NotPositive {
+        field: &'static str,
+        value: f64,
+    },
+    NotFinite {
+        field: &'static str,
+        value: f64,
+    },
+    OutOfRange {
+        field: &'static str,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    // `baseline - variance` would go negative, so spawned amounts or values
+    // could too.
+    VarianceExceedsBaseline {
+        field: &'static str,
+        baseline_field: &'static str,
+        variance: f64,
+        baseline: f64,
+    },
+}
+
+impl ConfigError {
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            ConfigError::Io { .. } | ConfigError::Parse { .. } | ConfigError::Env { .. } => None,
+            ConfigError::EmptyGrid { .. } => Some("grid_size"),
+            ConfigError::NotPositive { field, .. }
+            | ConfigError::NotFinite { field, .. }
+            | ConfigError::OutOfRange { field, .. }
+            | ConfigError::VarianceExceedsBaseline { field, .. } => Some(field),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            ConfigError::Parse { path, message } => write!(f, "{}: {message}", path.display()),
+            ConfigError::Env { var, message } => write!(f, "{var}: {message}"),
+            ConfigError::EmptyGrid { grid_size } => {
+                write!(f, "grid_size: grid must not be empty, got {grid_size:?}")
+            }
+            ConfigError::NotPositive { field, value } => {
+                write!(f, "{field}: must be greater than 0, got {value}")
+            }
+            ConfigError::NotFinite { field, value } => {
+                write!(f, "{field}: must be finite, got {value}")
+            }
+            ConfigError::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(f, "{field}: must be between {min} and {max}, got {value}"),
+            ConfigError::VarianceExceedsBaseline {
+                field,
+                baseline_field,
+                variance,
+                baseline,
+            } => write!(
+                f,
+                "{field}: {variance} is larger than {baseline_field} ({baseline}), \
+                 so baseline - variance goes negative"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// The Python side of `ConfigError`: one `ValueError` subclass per kind of
+// problem, all deriving from `ants_rs.ConfigError`, with the field path in a
+// `field` attribute. I/O errors stay `OSError`.
+// `create_exception!` in pyo3 0.22 checks its own `gil-refs` feature.
+#[cfg(feature = "python")]
+#[allow(unexpected_cfgs)]
+pub mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyValueError;
+
+    create_exception!(ants_rs, ConfigError, PyValueError);
+    create_exception!(ants_rs, EmptyGridError, ConfigError);
+    create_exception!(ants_rs, NotPositiveError, ConfigError);
+    create_exception!(ants_rs, NotFiniteError, ConfigError);
+    create_exception!(ants_rs, OutOfRangeError, ConfigError);
+    create_exception!(ants_rs, VarianceError, ConfigError);
+}
+
+#[cfg(feature = "python")]
+impl From<ConfigError> for PyErr {
+    fn from(error: ConfigError) -> PyErr {
+        let message = error.to_string();
+        let err = match &error {
+            ConfigError::Io { .. } => return PyIOError::new_err(message),
+            ConfigError::Parse { .. } | ConfigError::Env { .. } => {
+                exceptions::ConfigError::new_err(message)
+            }
+            ConfigError::EmptyGrid { .. } => exceptions::EmptyGridError::new_err(message),
+            ConfigError::NotPositive { .. } => exceptions::NotPositiveError::new_err(message),
+            ConfigError::NotFinite { .. } => exceptions::NotFiniteError::new_err(message),
+            ConfigError::OutOfRange { .. } => exceptions::OutOfRangeError::new_err(message),
+            ConfigError::VarianceExceedsBaseline { .. } => {
+                exceptions::VarianceError::new_err(message)
+            }
+        };
+        Python::with_gil(|py| {
+            // Setting an attribute on a fresh exception instance can't fail
+            let _ = err.value_bound(py).setattr("field", error.field());
+        });
+        err
+    }
+}
+
+#[cfg(feature = "python")]
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add(
+        "ConfigError",
+        py.get_type_bound::<exceptions::ConfigError>(),
+    )?;
+    m.add(
+        "EmptyGridError",
+        py.get_type_bound::<exceptions::EmptyGridError>(),
+    )?;
+    m.add(
+        "NotPositiveError",
+        py.get_type_bound::<exceptions::NotPositiveError>(),
+    )?;
+    m.add(
+        "NotFiniteError",
+        py.get_type_bound::<exceptions::NotFiniteError>(),
+    )?;
+    m.add(
+        "OutOfRangeError",
+        py.get_type_bound::<exceptions::OutOfRangeError>(),
+    )?;
+    m.add(
+        "VarianceError",
+        py.get_type_bound::<exceptions::VarianceError>(),
+    )?;
+    Ok(())
+}
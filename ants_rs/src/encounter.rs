@@ -0,0 +1,254 @@
+// What happens where ants of rival colonies meet, per `encounter_mode`.
+use std::borrow::{Borrow, BorrowMut};
+use std::collections::HashMap;
+
+use ndarray::Array2;
+use rand::Rng;
+
+use crate::colony::Colony;
+use crate::config::{EncounterMode, SimulationConfig};
+use crate::environment::{Environment, Layer, Position};
+
+const EMPTY: u16 = 0;
+const MIXED: u16 = u16::MAX;
+
+// Which colonies have ants on each cell, for "avoid": a cell holds
+// `EMPTY`, `MIXED` or one more than the index of the only colony there.
+#[derive(Clone, Debug)]
+pub struct Occupancy {
+    cells: Array2<u16>,
+}
+
+impl Occupancy {
+    pub fn build<C: Borrow<Colony>>(grid_size: (usize, usize), colonies: &[C]) -> Occupancy {
+        let mut cells = Array2::from_elem(grid_size, EMPTY);
+        for colony in colonies {
+            let colony = colony.borrow();
+            let tag = colony.id as u16 + 1;
+            for &(x, y) in &colony.ants.positions {
+                let cell = &mut cells[[x, y]];
+                *cell = match *cell {
+                    EMPTY => tag,
+                    occupant if occupant == tag => tag,
+                    _ => MIXED,
+                };
+            }
+        }
+        Occupancy { cells }
+    }
+
+    pub fn has_rival(&self, position: Position, colony: usize) -> bool {
+        match self.cells[[position.0, position.1]] {
+            EMPTY => false,
+            occupant => occupant != colony as u16 + 1,
+        }
+    }
+}
+
+// Applies the rules that act once everyone has moved: "steal" and "fight".
+pub fn resolve<C: BorrowMut<Colony>>(environment: &mut Environment, colonies: &mut [C]) {
+    if colonies.len() < 2 {
+        return;
+    }
+    match environment.config.encounter_mode {
+        EncounterMode::Steal => {
+            for group in meetings(colonies) {
+                steal(colonies, &group);
+            }
+        }
+        EncounterMode::Fight => fight(environment, colonies),
+        EncounterMode::Ignore | EncounterMode::Avoid => {}
+    }
+}
+
+// (position, colony, ant index) of every ant, sorted.
+fn all_ants<C: Borrow<Colony>>(colonies: &[C]) -> Vec<(Position, usize, usize)> {
+    let mut ants: Vec<(Position, usize, usize)> = colonies
+        .iter()
+        .enumerate()
+        .flat_map(|(colony, store)| {
+            let positions = &store.borrow().ants.positions;
+            positions
+                .iter()
+                .enumerate()
+                .map(move |(index, &position)| (position, colony, index))
+        })
+        .collect();
+    ants.sort_unstable();
+    ants
+}
+
+// (colony, ant index) of every ant on a cell shared with a rival, grouped by
+// cell in a fixed order: cells by position, then colonies, then ants.
+fn meetings<C: Borrow<Colony>>(colonies: &[C]) -> Vec<Vec<(usize, usize)>> {
+    all_ants(colonies)
+        .chunk_by(|a, b| a.0 == b.0)
+        .filter(|group| group.iter().any(|&(_, colony, _)| colony != group[0].1))
+        .map(|group| {
+            group
+                .iter()
+                .map(|&(_, colony, index)| (colony, index))
+                .collect()
+        })
+        .collect()
+}
+
+// Every forager with room left takes food from the rivals on its cell, in
+// order, until it is full. A robbed ant heading home empty-handed goes back
+// to foraging; a thief that fills up heads home.
+fn steal<C: BorrowMut<Colony>>(colonies: &mut [C], group: &[(usize, usize)]) {
+    for &(thief_colony, thief) in group {
+        for &(victim_colony, victim) in group {
+            if victim_colony == thief_colony {
+                continue;
+            }
+            let (room, returning) = {
+                let ants = &colonies[thief_colony].borrow().ants;
+                (
+                    ants.carrying_capacity[thief] - ants.food[thief],
+                    ants.returning_to_queen[thief],
+                )
+            };
+            if returning || room <= 0.0 {
+                break;
+            }
+            let ants = &mut colonies[victim_colony].borrow_mut().ants;
+            let taken = room.min(ants.food[victim]);
+            if taken <= 0.0 {
+                continue;
+            }
+            ants.food[victim] -= taken;
+            if ants.food[victim] <= 0.0 {
+                ants.food[victim] = 0.0;
+                ants.returning_to_queen[victim] = false;
+                ants.source_has_more_food[victim] = false;
+                ants.food_source_positions[victim] = None;
+            }
+
+            let ants = &mut colonies[thief_colony].borrow_mut().ants;
+            ants.food[thief] += taken;
+            if ants.food[thief] >= ants.carrying_capacity[thief] {
+                ants.returning_to_queen[thief] = true;
+            }
+        }
+    }
+}
+
+// Every ant, in `all_ants` order, that hasn't fought yet this step picks a
+// fight with probability `aggression` against a random rival on its own or a
+// neighbouring cell that hasn't fought either. The attacker wins with
+// probability `own / (own + rival)` strength, where an ant's strength is its
+// colony's times its caste's; then the loser and the winner die with their
+// `*_death_chance`. Both sides lay alarm pheromone where they stand. The
+// dead drop what they carried onto their cell and are removed once all
+// fights are over.
+fn fight<C: BorrowMut<Colony>>(environment: &mut Environment, colonies: &mut [C]) {
+    let combat = environment.config.combat.clone();
+    let castes = environment.config.castes.clone();
+
+    let ants = all_ants(colonies);
+    let mut by_cell: HashMap<Position, Vec<(usize, usize)>> = HashMap::new();
+    for &(position, colony, index) in &ants {
+        by_cell.entry(position).or_default().push((colony, index));
+    }
+    let mut fought: Vec<Vec<bool>> = colonies
+        .iter()
+        .map(|colony| vec![false; colony.borrow().ants.len()])
+        .collect();
+    let mut dead = fought.clone();
+
+    for &(position, colony, index) in &ants {
+        if fought[colony][index] {
+            continue;
+        }
+        let rivals: Vec<(usize, usize)> = reach(position, &environment.config)
+            .iter()
+            .filter_map(|cell| by_cell.get(cell))
+            .flatten()
+            .copied()
+            .filter(|&(other, other_index)| other != colony && !fought[other][other_index])
+            .collect();
+        if rivals.is_empty() {
+            continue;
+        }
+        let rng = &mut environment.combat_rng;
+        if rng.gen::<f64>() >= combat.aggression {
+            continue;
+        }
+        let (rival, rival_index) = rivals[rng.gen_range(0..rivals.len())];
+        fought[colony][index] = true;
+        fought[rival][rival_index] = true;
+
+        let strength = |colony: usize, index: usize| {
+            let caste = colonies[colony].borrow().ants.castes[index];
+            combat.strength_of(colony) * castes.get(caste).strength
+        };
+        let own = strength(colony, index);
+        let theirs = strength(rival, rival_index);
+        let ((winner, winner_index), (loser, loser_index)) =
+            if rng.gen::<f64>() * (own + theirs) < own {
+                ((colony, index), (rival, rival_index))
+            } else {
+                ((rival, rival_index), (colony, index))
+            };
+        if rng.gen::<f64>() < combat.loser_death_chance {
+            dead[loser][loser_index] = true;
+        }
+        if rng.gen::<f64>() < combat.winner_death_chance {
+            dead[winner][winner_index] = true;
+        }
+
+        for (side, side_index) in [(colony, index), (rival, rival_index)] {
+            let at = colonies[side].borrow().ants.positions[side_index];
+            environment.deposit(at, side, Layer::Alarm);
+        }
+    }
+
+    for (colony, dead) in colonies.iter_mut().zip(&dead) {
+        let colony = colony.borrow_mut();
+        // Highest index first, so the swaps never move an ant still to go
+        for index in (0..dead.len()).rev().filter(|&index| dead[index]) {
+            let ants = &colony.ants;
+            environment.drop_food(ants.positions[index], ants.food[index] as f32);
+            colony.ants.remove(index);
+            colony.casualties += 1;
+        }
+    }
+}
+
+// The cell at `position` and those one move away, each listed once, in move
+// order: at a reflecting edge several moves land on the same cell, and a
+// rival there must not be counted twice.
+fn reach(position: Position, config: &SimulationConfig) -> Vec<Position> {
+    let mut cells = vec![position];
+    for &(dx, dy) in config.neighborhood.moves() {
+        if let Some(cell) = config
+            .boundary_mode
+            .step(position, dx, dy, config.grid_size)
+        {
+            if !cells.contains(&cell) {
+                cells.push(cell);
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BoundaryMode, Neighborhood};
+
+    #[test]
+    fn reach_lists_each_cell_once_at_a_reflecting_corner() {
+        let config = SimulationConfig {
+            grid_size: (10, 10),
+            boundary_mode: BoundaryMode::Reflecting,
+            neighborhood: Neighborhood::Moore,
+            ..SimulationConfig::default()
+        };
+        let mut cells = reach((0, 0), &config);
+        cells.sort_unstable();
+        assert_eq!(cells, [(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+}
@@ -0,0 +1,835 @@
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "python")]
+use ndarray::ArrayView3;
+use ndarray::{s, Array2, Array3};
+#[cfg(feature = "python")]
+use numpy::{PyArray2, PyArray3};
+#[cfg(feature = "python")]
+use pyo3::{exceptions::PyValueError, prelude::*, types::IntoPyDict};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{EncounterMode, EvaporationMode, FoodSharing, SimulationConfig};
+use crate::encounter::Occupancy;
+use crate::food_index::FoodIndex;
+use crate::rng::{self, SimRng};
+use crate::terrain::Terrain;
+
+pub type Position = (usize, usize);
+
+// Pheromone values below this are snapped to zero after evaporating.
+const PHEROMONE_FLOOR: f32 = 1e-3;
+// Lazy evaporation brings every cell up to date and starts its history over
+// after this many steps, so the history stays small on long runs.
+const MAX_PENDING_DECAYS: usize = 1 << 12;
+
+// Wall hits `random_open_position` redraws before listing the open cells.
+const MAX_OPEN_POSITION_DRAWS: usize = 1000;
+
+// Shared by every environment, so a colony can tell a changed terrain from
+// another environment's terrain as well as from an edit.
+static NEXT_TERRAIN_REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_terrain_revision() -> u64 {
+    NEXT_TERRAIN_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+// Grid layers, same indices as the numpy array in `environment.Environment`.
+// With several colonies, each gets its own copy of the pheromone layers, so
+// colony `c`'s `Regular` layer sits at `Regular + c * pheromone_layers`; see
+// `Environment::layer_index`. `Alarm` only exists under
+// `encounter_mode = "fight"`, after the colony's other layers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Food = 0,
+    Regular = 1,
+    FoodPheromone = 2,
+    Rich = 3,
+    Trail = 4,
+    Alarm = 5,
+}
+
+#[cfg(feature = "python")]
+impl Layer {
+    fn parse(pheromone_type: &str) -> Option<Layer> {
+        match pheromone_type {
+            "regular" => Some(Layer::Regular),
+            "food" => Some(Layer::FoodPheromone),
+            "rich" => Some(Layer::Rich),
+            "trail" => Some(Layer::Trail),
+            "alarm" => Some(Layer::Alarm),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Serialize, Deserialize)]
+pub struct Environment {
+    // Written to snapshots once for the whole simulation; see `Snapshot::read`
+    #[serde(skip)]
+    pub config: SimulationConfig,
+    // Layer 0 is food. Then each colony in turn gets its pheromone layers:
+    // Regular, then Food, Rich and Trail if multiple pheromones are enabled,
+    // then Alarm if colonies fight.
+    pub grid: Array3<f32>,
+    pub food_positions: FoodIndex,
+    pub last_update_time: f64,
+    // Lazy evaporation only: the decay factor of every step since the
+    // history last started over, and how many of them each cell's
+    // pheromones have had applied so far.
+    pub decay_factors: Vec<f64>,
+    pub touched: Array2<usize>,
+    pub terrain: Terrain,
+    // Changes whenever `terrain` does, so colonies know to refresh their
+    // homing fields
+    #[serde(skip, default = "next_terrain_revision")]
+    pub terrain_revision: u64,
+    // Exclusive food sharing only: the colony that claimed each food cell
+    pub claims: Array2<Option<u8>>,
+    // Where each colony's ants stood at the start of the step, while
+    // `encounter_mode` is "avoid"
+    #[serde(skip)]
+    pub occupancy: Option<Occupancy>,
+    rng: SimRng,
+    // Drawn from by fights only, so a run without them keeps its sequence
+    pub combat_rng: SimRng,
+}
+
+// Applies a decay factor and snaps what's left below the floor to zero.
+fn decay(level: f32, decay_factor: f64) -> f32 {
+    let level = (level as f64 * decay_factor) as f32;
+    if level < PHEROMONE_FLOOR {
+        0.0
+    } else {
+        level
+    }
+}
+
+// Applies a run of per-step decay factors one step at a time, exactly as eager
+// evaporation would have, so both modes agree to the bit.
+fn replay(mut level: f32, decay_factors: &[f64]) -> f32 {
+    for &decay_factor in decay_factors {
+        if level == 0.0 {
+            break;
+        }
+        level = decay(level, decay_factor);
+    }
+    level
+}
+
+impl Environment {
+    pub fn new(config: SimulationConfig, seed: u64, terrain: Terrain) -> Environment {
+        let (grid_width, grid_height) = config.grid_size;
+        let num_layers = 1 + config.num_colonies * pheromone_layers(&config);
+        let claims = match config.food_sharing {
+            FoodSharing::Shared => Array2::from_elem((0, 0), None),
+            FoodSharing::Exclusive => Array2::from_elem((grid_width, grid_height), None),
+        };
+        let touched = match config.pheromone_evaporation_mode {
+            EvaporationMode::Eager => Array2::zeros((0, 0)),
+            EvaporationMode::Lazy => Array2::zeros((grid_width, grid_height)),
+        };
+        Environment {
+            grid: Array3::zeros((grid_width, grid_height, num_layers)),
+            food_positions: FoodIndex::new(config.grid_size),
+            last_update_time: 0.0,
+            decay_factors: Vec::new(),
+            touched,
+            terrain,
+            terrain_revision: next_terrain_revision(),
+            claims,
+            occupancy: None,
+            rng: rng::stream(seed, rng::ENVIRONMENT_STREAM),
+            combat_rng: rng::stream(seed, rng::COMBAT_STREAM),
+            config,
+        }
+    }
+
+    pub fn num_layers(&self) -> usize {
+        self.grid.dim().2
+    }
+
+    // Where `colony`'s copy of `layer` sits in the grid, or `None` for the
+    // layers that only exist with multiple pheromones or while fighting.
+    pub fn layer_index(&self, colony: usize, layer: Layer) -> Option<usize> {
+        let base = base_pheromone_layers(&self.config);
+        let offset = match layer {
+            Layer::Food => return Some(0),
+            Layer::Alarm if self.config.encounter_mode == EncounterMode::Fight => base,
+            Layer::Alarm => return None,
+            _ => layer as usize - 1,
+        };
+        (offset < pheromone_layers(&self.config))
+            .then(|| 1 + colony * pheromone_layers(&self.config) + offset)
+    }
+
+    pub fn update(&mut self, current_time: f64) {
+        self.spawn_food();
+        self.evaporate_pheromones(current_time);
+        self.last_update_time = current_time;
+    }
+
+    pub fn spawn_food(&mut self) {
+        if self.rng.gen::<f64>() < self.config.food.spawn_chance {
+            let num_foods = self.calculate_number_of_foods_to_spawn();
+            for _ in 0..num_foods {
+                let Some(pos) = self.random_open_position() else {
+                    // Walled over completely; nowhere for food to go
+                    return;
+                };
+                let food_amount = self.calculate_food_value();
+                self.grid[[pos.0, pos.1, Layer::Food as usize]] += food_amount as f32;
+                self.food_positions.insert(pos);
+            }
+        }
+    }
+
+    // Swaps in a new map, clearing any food that ends up inside a wall.
+    pub fn set_terrain(&mut self, terrain: Terrain) {
+        self.terrain = terrain;
+        self.terrain_changed();
+    }
+
+    pub fn set_wall(&mut self, position: Position, wall: bool) {
+        self.terrain.walls[[position.0, position.1]] = wall;
+        self.terrain_changed();
+    }
+
+    fn terrain_changed(&mut self) {
+        let buried: Vec<Position> = self
+            .food_positions
+            .iter()
+            .filter(|&pos| self.terrain.is_wall(pos))
+            .collect();
+        for pos in buried {
+            self.take_food(pos, self.food_amount(pos));
+        }
+        self.terrain_revision = next_terrain_revision();
+    }
+
+    // Redraws positions that land on a wall a bounded number of times, then
+    // picks among the open cells directly. `None` once `set_wall` has walled
+    // over every cell.
+    fn random_open_position(&mut self) -> Option<Position> {
+        let (grid_width, grid_height) = self.config.grid_size;
+        for _ in 0..MAX_OPEN_POSITION_DRAWS {
+            let pos = (
+                self.rng.gen_range(0..grid_width),
+                self.rng.gen_range(0..grid_height),
+            );
+            if self.terrain.is_open(pos) {
+                return Some(pos);
+            }
+        }
+        let open_cells: Vec<Position> = self.terrain.open_cells().collect();
+        if open_cells.is_empty() {
+            return None;
+        }
+        Some(open_cells[self.rng.gen_range(0..open_cells.len())])
+    }
+
+    pub fn evaporate_pheromones(&mut self, current_time: f64) {
+        let time_elapsed = current_time - self.last_update_time;
+        let decay_factor = self.decay_factor(time_elapsed);
+        if self.lazy() {
+            // Cells catch up when they are next read or written
+            self.decay_factors.push(decay_factor);
+            if self.decay_factors.len() > MAX_PENDING_DECAYS {
+                self.catch_up_all();
+                self.decay_factors.clear();
+                self.touched.fill(0);
+            }
+            return;
+        }
+        // Evaporate pheromones in all layers except the food layer (layer 0)
+        self.grid
+            .slice_mut(s![.., .., 1..])
+            .mapv_inplace(|level| decay(level, decay_factor));
+    }
+
+    fn lazy(&self) -> bool {
+        self.config.pheromone_evaporation_mode == EvaporationMode::Lazy
+    }
+
+    fn decay_factor(&self, time_elapsed: f64) -> f64 {
+        (-self.config.pheromone_evaporation_rate * time_elapsed).exp()
+    }
+
+    // Lazy mode: applies the steps one cell's pheromone layers have missed.
+    fn catch_up(&mut self, position: Position) {
+        let stamp = &mut self.touched[[position.0, position.1]];
+        let pending = &self.decay_factors[*stamp..];
+        *stamp = self.decay_factors.len();
+        for level in self.grid.slice_mut(s![position.0, position.1, 1..]) {
+            *level = replay(*level, pending);
+        }
+    }
+
+    // Lazy mode: brings every cell up to date so the raw grid can be read
+    // directly. A no-op in eager mode.
+    pub fn catch_up_all(&mut self) {
+        if !self.lazy() {
+            return;
+        }
+        let (grid_width, grid_height) = self.config.grid_size;
+        for x in 0..grid_width {
+            for y in 0..grid_height {
+                self.catch_up((x, y));
+            }
+        }
+    }
+
+    fn calculate_number_of_foods_to_spawn(&mut self) -> usize {
+        let baseline = self.config.food.spawn_baseline;
+        let variance = self.config.food.spawn_variance;
+        let num_foods = self
+            .rng
+            .gen_range(baseline - variance..=baseline + variance);
+        num_foods.max(0) as usize
+    }
+
+    fn calculate_food_value(&mut self) -> f64 {
+        let baseline = self.config.food.value_baseline;
+        let variance = self.config.food.value_variance;
+        let food_value = self
+            .rng
+            .gen_range(baseline - variance..=baseline + variance);
+        food_value.max(1.0)
+    }
+
+    pub fn deposit(&mut self, position: Position, colony: usize, layer: Layer) {
+        if let Some(index) = self.layer_index(colony, layer) {
+            if self.lazy() {
+                self.catch_up(position);
+            }
+            let intensity = self.config.pheromone_initial_intensity as f32;
+            let cell = &mut self.grid[[position.0, position.1, index]];
+            if layer == Layer::Trail {
+                *cell += intensity;
+            } else {
+                *cell = intensity;
+            }
+        }
+    }
+
+    pub fn level(&self, position: Position, colony: usize, layer: Layer) -> f32 {
+        let Some(index) = self.layer_index(colony, layer) else {
+            return 0.0;
+        };
+        let level = self.grid[[position.0, position.1, index]];
+        if self.lazy() && layer != Layer::Food {
+            let stamp = self.touched[[position.0, position.1]];
+            replay(level, &self.decay_factors[stamp..])
+        } else {
+            level
+        }
+    }
+
+    // Pheromone level as `get_pheromone_level` reports it. Like the Python
+    // version, the trail layer is write-only and always reads as zero.
+    pub fn pheromone_level(&self, position: Position, colony: usize, layer: Layer) -> f32 {
+        match layer {
+            Layer::Trail => 0.0,
+            _ => self.level(position, colony, layer),
+        }
+    }
+
+    pub fn food_amount(&self, position: Position) -> f32 {
+        self.grid[[position.0, position.1, Layer::Food as usize]]
+    }
+
+    pub fn take_food(&mut self, position: Position, amount: f32) {
+        let cell = &mut self.grid[[position.0, position.1, Layer::Food as usize]];
+        let new_amount = *cell - amount;
+        if new_amount <= 0.0 {
+            *cell = 0.0;
+            self.food_positions.remove(position);
+            if let Some(claim) = self.claims.get_mut([position.0, position.1]) {
+                *claim = None;
+            }
+        } else {
+            *cell = new_amount;
+        }
+    }
+
+    // Food spilled by an ant that died away from the nest.
+    pub fn drop_food(&mut self, position: Position, amount: f32) {
+        if amount <= 0.0 {
+            return;
+        }
+        self.grid[[position.0, position.1, Layer::Food as usize]] += amount;
+        self.food_positions.insert(position);
+    }
+
+    // Scans the cells within `radius` steps of `position` (the Manhattan
+    // diamond of the Python implementation on the default grid) in the same
+    // order as Python, wrapping around the grid edges on the torus and
+    // stopping at them otherwise.
+    pub fn food_within_radius(&self, position: Position, radius: usize) -> Vec<Position> {
+        let grid_size = self.config.grid_size;
+        let boundary = self.config.boundary_mode;
+        let neighborhood = self.config.neighborhood;
+        let mut positions = Vec::new();
+        let (min, max) = (-(radius as i64), radius as i64);
+        for dx in min..=max {
+            for dy in min..=max {
+                if neighborhood.norm(dx, dy) > radius {
+                    continue;
+                }
+                if let Some(pos) = boundary.locate(position, dx, dy, grid_size) {
+                    if self.food_positions.contains(pos) {
+                        positions.push(pos);
+                    }
+                }
+            }
+        }
+        positions
+    }
+
+    // Same result as `min` by distance over `food_within_radius`, answered
+    // from the food index unless the area wraps onto itself.
+    pub fn closest_food_within_radius(
+        &self,
+        position: Position,
+        radius: usize,
+    ) -> Option<Position> {
+        let (grid_width, grid_height) = self.config.grid_size;
+        let boundary = self.config.boundary_mode;
+        let neighborhood = self.config.neighborhood;
+        if !boundary.wraps() || 2 * radius < grid_width.min(grid_height) {
+            return self.food_positions.closest_within(
+                position,
+                radius,
+                boundary.wraps(),
+                neighborhood,
+            );
+        }
+        let grid_size = self.config.grid_size;
+        // `min_by_key` keeps the first minimum, like Python's `min`
+        self.food_within_radius(position, radius)
+            .into_iter()
+            .min_by_key(|&pos| boundary.distance(position, pos, grid_size, neighborhood))
+    }
+
+    // Marks a food cell as `colony`'s under exclusive food sharing; a no-op
+    // when food is shared or the cell is already claimed.
+    pub fn claim_food(&mut self, position: Position, colony: usize) {
+        if let Some(claim) = self.claims.get_mut([position.0, position.1]) {
+            claim.get_or_insert(colony as u8);
+        }
+    }
+
+    // Whether `colony`'s ants may see and take the food at `position`.
+    pub fn food_visible_to(&self, position: Position, colony: usize) -> bool {
+        match self.claims.get([position.0, position.1]) {
+            Some(&Some(owner)) => owner as usize == colony,
+            _ => true,
+        }
+    }
+
+    // `closest_food_within_radius` as `colony` sees it: food claimed by a
+    // rival is skipped, which takes the scan instead of the food index.
+    pub fn closest_visible_food(
+        &self,
+        position: Position,
+        radius: usize,
+        colony: usize,
+    ) -> Option<Position> {
+        if self.config.food_sharing == FoodSharing::Shared {
+            return self.closest_food_within_radius(position, radius);
+        }
+        let grid_size = self.config.grid_size;
+        let boundary = self.config.boundary_mode;
+        let neighborhood = self.config.neighborhood;
+        self.food_within_radius(position, radius)
+            .into_iter()
+            .filter(|&pos| self.food_visible_to(pos, colony))
+            .min_by_key(|&pos| boundary.distance(position, pos, grid_size, neighborhood))
+    }
+
+    // For each cell, the colony whose pheromones are strongest there, summed
+    // over its layers except `Alarm`; -1 where no colony has any, or where
+    // the strongest are tied.
+    pub fn territory(&self) -> Array2<i16> {
+        const MARKS: [Layer; 4] = [
+            Layer::Regular,
+            Layer::FoodPheromone,
+            Layer::Rich,
+            Layer::Trail,
+        ];
+        Array2::from_shape_fn(self.config.grid_size, |(x, y)| {
+            let mut owner = -1;
+            let mut strongest = 0.0;
+            for colony in 0..self.config.num_colonies {
+                let total: f32 = MARKS
+                    .iter()
+                    .map(|&layer| self.level((x, y), colony, layer))
+                    .sum();
+                if total > strongest {
+                    owner = colony as i16;
+                    strongest = total;
+                } else if total > 0.0 && total == strongest {
+                    owner = -1;
+                }
+            }
+            owner
+        })
+    }
+}
+
+// Pheromone layers per colony: all four, or just `Regular` when
+// `enable_multiple_pheromones` is off, plus `Alarm` when fighting.
+fn pheromone_layers(config: &SimulationConfig) -> usize {
+    let alarm = (config.encounter_mode == EncounterMode::Fight) as usize;
+    base_pheromone_layers(config) + alarm
+}
+
+fn base_pheromone_layers(config: &SimulationConfig) -> usize {
+    if config.enable_multiple_pheromones {
+        4
+    } else {
+        1
+    }
+}
+
+// Toroidal distance, as in `Ant.manhattan_distance`.
+pub fn manhattan_distance(pos1: Position, pos2: Position, grid_size: (usize, usize)) -> usize {
+    let (grid_width, grid_height) = grid_size;
+    let dx = pos1.0.abs_diff(pos2.0);
+    let dy = pos1.1.abs_diff(pos2.1);
+    dx.min(grid_width - dx) + dy.min(grid_height - dy)
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Environment {
+    #[new]
+    #[pyo3(signature = (config, seed=None))]
+    fn py_new(config: &Bound<'_, PyAny>, seed: Option<u64>) -> PyResult<Self> {
+        let config = SimulationConfig::from_py(config)?;
+        let terrain = Terrain::for_config(&config)?;
+        Ok(Environment::new(config, rng::resolve_seed(seed), terrain))
+    }
+
+    #[getter(config)]
+    fn get_config(&self) -> SimulationConfig {
+        self.config.clone()
+    }
+
+    // A live, read-only numpy view of the layers, shaped (width, height,
+    // layers) like the Python `grid`. It aliases the Rust buffer instead of
+    // copying it, so its values change as the simulation steps; take
+    // `.copy()` to keep a frame. In lazy mode the grid is brought up to date
+    // first, and the view only reflects later steps once fetched again.
+    #[getter(grid)]
+    fn get_grid<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray3<f32>>> {
+        slf.borrow_mut().catch_up_all();
+        readonly_view(slf.borrow().grid.view(), slf)
+    }
+
+    // A (width, height) bool array, true on walls. Unlike `grid` it is a
+    // copy.
+    #[getter(terrain)]
+    fn get_terrain<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<bool>> {
+        PyArray2::from_array_bound(py, &self.terrain.walls)
+    }
+
+    fn is_wall(&self, position: Position) -> PyResult<bool> {
+        self.check_position(position)?;
+        Ok(self.terrain.is_wall(position))
+    }
+
+    // Adds or removes a wall; returning ants re-plan their way home on the
+    // next step.
+    #[pyo3(name = "set_wall", signature = (position, wall=true))]
+    fn py_set_wall(&mut self, position: Position, wall: bool) -> PyResult<()> {
+        self.check_position(position)?;
+        self.set_wall(position, wall);
+        Ok(())
+    }
+
+    // Replaces the terrain with a map file, as `terrain_map` would load it.
+    fn load_terrain(&mut self, path: PathBuf) -> PyResult<()> {
+        let terrain = Terrain::load(&path, self.config.grid_size)?;
+        self.set_terrain(terrain);
+        Ok(())
+    }
+
+    #[getter(last_update_time)]
+    fn get_last_update_time(&self) -> f64 {
+        self.last_update_time
+    }
+
+    #[pyo3(name = "update")]
+    fn py_update(&mut self, current_time: f64) {
+        self.update(current_time);
+    }
+
+    #[pyo3(name = "spawn_food")]
+    fn py_spawn_food(&mut self) {
+        self.spawn_food();
+    }
+
+    #[pyo3(name = "evaporate_pheromones")]
+    fn py_evaporate_pheromones(&mut self, current_time: f64) {
+        self.evaporate_pheromones(current_time);
+    }
+
+    // `colony` picks whose layers to use when there are several colonies.
+    #[pyo3(signature = (position, pheromone_type="regular", colony=0))]
+    fn add_pheromone(
+        &mut self,
+        position: Position,
+        pheromone_type: &str,
+        colony: usize,
+    ) -> PyResult<()> {
+        self.check_position(position)?;
+        if let Some(layer) = Layer::parse(pheromone_type) {
+            if colony < self.config.num_colonies {
+                self.deposit(position, colony, layer);
+            }
+        }
+        Ok(())
+    }
+
+    #[pyo3(signature = (position, pheromone_type="regular", colony=0))]
+    fn get_pheromone_level(
+        &self,
+        position: Position,
+        pheromone_type: &str,
+        colony: usize,
+    ) -> PyResult<f32> {
+        self.check_position(position)?;
+        Ok(match Layer::parse(pheromone_type) {
+            Some(layer) if colony < self.config.num_colonies => {
+                self.pheromone_level(position, colony, layer)
+            }
+            _ => 0.0,
+        })
+    }
+
+    fn get_food_amount(&self, position: Position) -> PyResult<f32> {
+        self.check_position(position)?;
+        Ok(self.food_amount(position))
+    }
+
+    fn remove_food(&mut self, position: Position, amount: f32) -> PyResult<()> {
+        self.check_position(position)?;
+        self.take_food(position, amount);
+        Ok(())
+    }
+
+    fn get_food_positions(&self) -> Vec<Position> {
+        self.food_positions.iter().collect()
+    }
+
+    // The pheromone layers of `grid`, also a live view.
+    fn get_pheromone_grid<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyArray3<f32>>> {
+        slf.borrow_mut().catch_up_all();
+        readonly_view(slf.borrow().grid.slice(s![.., .., 1..]), slf)
+    }
+
+    // A (width, height) int16 array of `territory`: the index of the colony
+    // holding each cell, or -1. A copy, like `terrain`.
+    fn get_territory_map<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<i16>> {
+        PyArray2::from_array_bound(py, &self.territory())
+    }
+
+    fn get_food_positions_within_radius(
+        &self,
+        position: Position,
+        radius: usize,
+    ) -> PyResult<Vec<Position>> {
+        self.check_position(position)?;
+        Ok(self.food_within_radius(position, radius))
+    }
+
+    fn get_closest_food_within_radius(
+        &self,
+        position: Position,
+        radius: usize,
+    ) -> PyResult<Option<Position>> {
+        self.check_position(position)?;
+        Ok(self.closest_food_within_radius(position, radius))
+    }
+}
+
+#[cfg(feature = "python")]
+impl Environment {
+    // Positions from Python are checked before they index the grid.
+    fn check_position(&self, position: Position) -> PyResult<()> {
+        let (grid_width, grid_height) = self.config.grid_size;
+        if position.0 < grid_width && position.1 < grid_height {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "position {position:?} is outside the {grid_width}x{grid_height} grid"
+            )))
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+fn readonly_view<'py>(
+    view: ArrayView3<'_, f32>,
+    environment: &Bound<'py, Environment>,
+) -> PyResult<Bound<'py, PyArray3<f32>>> {
+    let py = environment.py();
+    // SAFETY: the array aliases a buffer that Rust later mutates, which is
+    // sound because the two never overlap. The grid is allocated once in
+    // `Environment::new` and only written in place, and the array holds
+    // `environment` as its base object, so the buffer outlives the view.
+    // Rust only writes to it through a `PyRefMut` taken with the GIL held,
+    // and no borrow of a Python-owned `Environment` is kept across a release
+    // of the GIL or past the end of the call, while numpy only reads the
+    // array with the GIL held. A reader therefore never sees a write in
+    // progress, only the values left by the last step.
+    let array = unsafe { PyArray3::borrow_from_array_bound(&view, environment.clone().into_any()) };
+    array.call_method(
+        "setflags",
+        (),
+        Some(&[("write", false)].into_py_dict_bound(py)),
+    )?;
+    Ok(array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BoundaryMode, EvaporationMode, Neighborhood};
+    use rand::SeedableRng;
+
+    const BOUNDARIES: [BoundaryMode; 2] = [BoundaryMode::Torus, BoundaryMode::Bounded];
+    const NEIGHBORHOODS: [Neighborhood; 3] = [
+        Neighborhood::VonNeumann,
+        Neighborhood::Moore,
+        Neighborhood::Hex,
+    ];
+
+    fn environment(config: SimulationConfig) -> Environment {
+        let terrain = Terrain::open(config.grid_size);
+        Environment::new(config, 0, terrain)
+    }
+
+    #[test]
+    fn food_index_matches_scan() {
+        let mut rng = SimRng::seed_from_u64(0);
+        for grid_size in [(17, 12), (24, 24), (9, 31)] {
+            for boundary_mode in BOUNDARIES {
+                for neighborhood in NEIGHBORHOODS {
+                    let mut environment = environment(SimulationConfig {
+                        grid_size,
+                        boundary_mode,
+                        neighborhood,
+                        ..SimulationConfig::default()
+                    });
+                    for _ in 0..rng.gen_range(0..40) {
+                        let position =
+                            (rng.gen_range(0..grid_size.0), rng.gen_range(0..grid_size.1));
+                        environment.drop_food(position, 1.0);
+                    }
+                    for _ in 0..200 {
+                        let position =
+                            (rng.gen_range(0..grid_size.0), rng.gen_range(0..grid_size.1));
+                        let radius = rng.gen_range(0..grid_size.0.max(grid_size.1));
+                        let scanned = environment
+                            .food_within_radius(position, radius)
+                            .into_iter()
+                            .min_by_key(|&pos| {
+                                boundary_mode.distance(position, pos, grid_size, neighborhood)
+                            });
+                        assert_eq!(
+                            environment.closest_food_within_radius(position, radius),
+                            scanned,
+                            "{grid_size:?} {boundary_mode:?} {neighborhood:?} \
+                             from {position:?} within {radius}",
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lazy_evaporation_matches_eager() {
+        let config = SimulationConfig {
+            grid_size: (20, 15),
+            num_colonies: 2,
+            enable_multiple_pheromones: true,
+            ..SimulationConfig::default()
+        };
+        let mut eager = environment(config.clone());
+        let mut lazy = environment(SimulationConfig {
+            pheromone_evaporation_mode: EvaporationMode::Lazy,
+            ..config.clone()
+        });
+        let layers = [
+            Layer::Regular,
+            Layer::FoodPheromone,
+            Layer::Rich,
+            Layer::Trail,
+        ];
+        let (grid_width, grid_height) = config.grid_size;
+        let mut rng = SimRng::seed_from_u64(0);
+        let mut current_time = 0.0;
+        // Long enough for the lazy history to start over once
+        for _ in 0..MAX_PENDING_DECAYS + 200 {
+            current_time += config.animation.step_interval;
+            eager.update(current_time);
+            lazy.update(current_time);
+            for _ in 0..rng.gen_range(0..10) {
+                let position = (rng.gen_range(0..grid_width), rng.gen_range(0..grid_height));
+                let colony = rng.gen_range(0..config.num_colonies);
+                let layer = layers[rng.gen_range(0..layers.len())];
+                eager.deposit(position, colony, layer);
+                lazy.deposit(position, colony, layer);
+            }
+        }
+
+        for x in 0..grid_width {
+            for y in 0..grid_height {
+                for colony in 0..config.num_colonies {
+                    for layer in layers {
+                        let expected = eager.level((x, y), colony, layer);
+                        let actual = lazy.level((x, y), colony, layer);
+                        assert_eq!(
+                            expected.to_bits(),
+                            actual.to_bits(),
+                            "({x}, {y}) {layer:?} of colony {colony}: {expected} vs {actual}",
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_food_spawns_once_everything_is_walled() {
+        let mut config = SimulationConfig {
+            grid_size: (4, 3),
+            ..SimulationConfig::default()
+        };
+        config.food.spawn_chance = 1.0;
+        let mut environment = environment(config);
+        for x in 0..4 {
+            for y in 0..3 {
+                if (x, y) != (2, 1) {
+                    environment.set_wall((x, y), true);
+                }
+            }
+        }
+        environment.spawn_food();
+        assert_eq!(
+            environment.food_positions.iter().collect::<Vec<_>>(),
+            [(2, 1)]
+        );
+
+        environment.set_wall((2, 1), true);
+        environment.spawn_food();
+        assert_eq!(environment.food_positions.iter().count(), 0);
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Neighborhood;
+use crate::environment::Position;
+
+// Food sources, kept both as a flat set and bucketed by column with each
+// column's rows sorted. The column buckets let `closest_within` look at one
+// column per `dx` instead of probing every cell within the perception radius.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FoodIndex {
+    positions: BTreeSet<Position>,
+    columns: Vec<BTreeSet<usize>>,
+    grid_size: (usize, usize),
+}
+
+impl FoodIndex {
+    pub fn new(grid_size: (usize, usize)) -> FoodIndex {
+        FoodIndex {
+            positions: BTreeSet::new(),
+            columns: vec![BTreeSet::new(); grid_size.0],
+            grid_size,
+        }
+    }
+
+    pub fn insert(&mut self, position: Position) {
+        if self.positions.insert(position) {
+            self.columns[position.0].insert(position.1);
+        }
+    }
+
+    pub fn remove(&mut self, position: Position) {
+        if self.positions.remove(&position) {
+            self.columns[position.0].remove(&position.1);
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.positions.contains(&position)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.positions.iter().copied()
+    }
+
+    // Closest food within `radius` steps of `position` under `neighborhood`,
+    // on the torus if `wrap` is set, breaking ties like `min` over the scan
+    // in `get_food_positions_within_radius`: lowest `dx`, then lowest `dy`.
+    //
+    // On the torus, only valid while the scanned square doesn't wrap onto
+    // itself, i.e. while `2 * radius < min(width, height)`; the caller falls
+    // back to the scan otherwise.
+    pub fn closest_within(
+        &self,
+        position: Position,
+        radius: usize,
+        wrap: bool,
+        neighborhood: Neighborhood,
+    ) -> Option<Position> {
+        let (grid_width, grid_height) = self.grid_size;
+        let (x0, y0) = position;
+        let radius = radius as i64;
+        // (distance, dx, dy) of the best candidate so far
+        let mut best: Option<(usize, i64, i64)> = None;
+
+        for dx in -radius..=radius {
+            // Rows of this column closest to `position`; the distance grows
+            // moving away from them either way
+            let (low, high) = neighborhood.nearest_rows(dx);
+            let nearest = neighborhood.norm(dx, low);
+            if nearest > radius as usize || best.is_some_and(|(distance, _, _)| nearest >= distance)
+            {
+                // Nothing in this column can beat the current best
+                continue;
+            }
+            let x = x0 as i64 + dx;
+            if !wrap && !(0..grid_width as i64).contains(&x) {
+                continue;
+            }
+            let column = &self.columns[x.rem_euclid(grid_width as i64) as usize];
+            if column.is_empty() {
+                continue;
+            }
+            let rows = Rows {
+                column,
+                y0: y0 as i64,
+                height: grid_height as i64,
+                wrap,
+            };
+
+            let dy = match rows.first(low, high) {
+                Some(dy) => dy,
+                None => {
+                    // The nearest food on either side, by distance; the lower
+                    // side comes first in scan order, so it wins ties
+                    let below = rows.last(-radius, low - 1);
+                    let above = rows.first(high + 1, radius);
+                    match (below, above) {
+                        (Some(below), Some(above))
+                            if neighborhood.norm(dx, above) < neighborhood.norm(dx, below) =>
+                        {
+                            above
+                        }
+                        (Some(below), _) => below,
+                        (None, Some(above)) => above,
+                        (None, None) => continue,
+                    }
+                }
+            };
+            let distance = neighborhood.norm(dx, dy);
+            if distance > radius as usize {
+                continue;
+            }
+            // Columns are visited in increasing dx, so only a strictly
+            // shorter distance replaces an earlier candidate.
+            if best.is_none_or(|(best_distance, _, _)| distance < best_distance) {
+                best = Some((distance, dx, dy));
+            }
+        }
+
+        best.map(|(_, dx, dy)| {
+            (
+                (x0 as i64 + dx).rem_euclid(grid_width as i64) as usize,
+                (y0 as i64 + dy).rem_euclid(grid_height as i64) as usize,
+            )
+        })
+    }
+}
+
+// One column of food around row `y0`, searched by offset `dy`.
+struct Rows<'a> {
+    column: &'a BTreeSet<usize>,
+    y0: i64,
+    height: i64,
+    wrap: bool,
+}
+
+impl Rows<'_> {
+    // Lowest `dy` in `low..=high` with food
+    fn first(&self, low: i64, high: i64) -> Option<i64> {
+        self.ranges(low, high)
+            .into_iter()
+            .flatten()
+            .find_map(|range| self.column.range(range).next().map(|&y| self.dy(y, low)))
+    }
+
+    // Highest `dy` in `low..=high` with food
+    fn last(&self, low: i64, high: i64) -> Option<i64> {
+        self.ranges(low, high)
+            .into_iter()
+            .rev()
+            .flatten()
+            .find_map(|range| {
+                self.column
+                    .range(range)
+                    .next_back()
+                    .map(|&y| self.dy(y, low))
+            })
+    }
+
+    // The rows `y0 + low..=y0 + high` as up to two ranges of the column in
+    // `dy` order: split where they wrap on the torus, clipped to the grid
+    // otherwise.
+    fn ranges(&self, low: i64, high: i64) -> [Option<Range<usize>>; 2] {
+        if low > high {
+            return [None, None];
+        }
+        if !self.wrap {
+            let start = (self.y0 + low).max(0);
+            let end = (self.y0 + high + 1).min(self.height);
+            return [(start < end).then_some(start as usize..end as usize), None];
+        }
+        let start = (self.y0 + low).rem_euclid(self.height);
+        let end = start + high - low + 1;
+        if end <= self.height {
+            [Some(start as usize..end as usize), None]
+        } else {
+            [
+                Some(start as usize..self.height as usize),
+                Some(0..(end - self.height) as usize),
+            ]
+        }
+    }
+
+    // The offset of row `y` from `y0`, counted from `low` on the torus
+    fn dy(&self, y: usize, low: i64) -> i64 {
+        if self.wrap {
+            low + (y as i64 - self.y0 - low).rem_euclid(self.height)
+        } else {
+            y as i64 - self.y0
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// Distances to the nest over open cells, so returning ants can walk around
+// walls instead of pressing into them like the greedy `move_towards`.
+use std::collections::VecDeque;
+
+use ndarray::Array2;
+
+use crate::config::{BoundaryMode, Neighborhood};
+use crate::environment::Position;
+use crate::terrain::Terrain;
+
+const UNREACHABLE: u32 = u32::MAX;
+
+#[derive(Clone, Debug)]
+pub struct DistanceField {
+    // Steps from each cell to the target, across the edges wherever the
+    // boundary lets ants cross; `UNREACHABLE` for walls and walled-off cells
+    distances: Array2<u32>,
+    boundary: BoundaryMode,
+    neighborhood: Neighborhood,
+    // The steps `move_towards` can take, diagonals included on the square
+    // grids, so following the field is as fast as the greedy walk on open
+    // ground. Straight steps come first, which breaks ties between equally
+    // short routes towards them.
+    moves: &'static [(i64, i64)],
+}
+
+impl DistanceField {
+    // Breadth-first search out of `target`. Every move is reversible, even a
+    // reflected one, so distances out of the target are distances into it.
+    pub fn compute(
+        terrain: &Terrain,
+        target: Position,
+        boundary: BoundaryMode,
+        neighborhood: Neighborhood,
+    ) -> DistanceField {
+        let moves = neighborhood.homing_moves();
+        let grid_size = terrain.walls.dim();
+        let mut distances = Array2::from_elem(grid_size, UNREACHABLE);
+        let mut queue = VecDeque::new();
+        if terrain.is_open(target) {
+            distances[[target.0, target.1]] = 0;
+            queue.push_back(target);
+        }
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[[position.0, position.1]] + 1;
+            for next in moves
+                .iter()
+                .filter_map(|&step| terrain.step(position, step, boundary, neighborhood))
+            {
+                if distances[[next.0, next.1]] == UNREACHABLE {
+                    distances[[next.0, next.1]] = distance;
+                    queue.push_back(next);
+                }
+            }
+        }
+        DistanceField {
+            distances,
+            boundary,
+            neighborhood,
+            moves,
+        }
+    }
+
+    pub fn distance(&self, position: Position) -> Option<u32> {
+        let distance = self.distances[[position.0, position.1]];
+        (distance != UNREACHABLE).then_some(distance)
+    }
+
+    // The first neighbour one step closer to the target, or `None` at the
+    // target itself and on cells the target can't be reached from. `terrain`
+    // is the one the field was computed over.
+    pub fn next_step(&self, position: Position, terrain: &Terrain) -> Option<Position> {
+        let distance = self.distance(position)?;
+        if distance == 0 {
+            return None;
+        }
+        self.moves
+            .iter()
+            .filter_map(|&step| terrain.step(position, step, self.boundary, self.neighborhood))
+            .find(|&next| self.distance(next) == Some(distance - 1))
+    }
+}
@@ -1,13 +1,42 @@
+// The pyo3 0.22 macros trip this lint on methods returning `PyResult`.
+#![allow(clippy::useless_conversion)]
+
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use std::sync::{Arc, Mutex};
 
+pub mod ant;
+mod ant_store;
+pub mod batch;
+mod boundary;
+pub mod colony;
+pub mod config;
+pub mod config_error;
+mod encounter;
+pub mod environment;
+mod food_index;
+mod homing;
+mod neighborhood;
+#[cfg(feature = "python")]
+mod recorder;
+pub mod render;
+pub mod rng;
+pub mod settings;
+pub mod simulation;
+pub mod snapshot;
+pub mod terrain;
+pub mod viewer;
+
 // Worker struct: represents a simple worker with an id and state.
+#[cfg(feature = "python")]
 #[pyclass]
 struct Worker {
     id: usize,
     state: usize,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl Worker {
     #[new]
@@ -31,11 +60,13 @@ impl Worker {
 }
 
 // Aggregator struct: collects worker states.
+#[cfg(feature = "python")]
 #[pyclass]
 struct Aggregator {
     states: Arc<Mutex<Vec<usize>>>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl Aggregator {
     #[new]
@@ -59,9 +90,27 @@ impl Aggregator {
 }
 
 /// Python module.
+#[cfg(feature = "python")]
 #[pymodule]
 fn ants_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Worker>()?;
     m.add_class::<Aggregator>()?;
+    m.add_class::<config::SimulationConfig>()?;
+    m.add_class::<config::FoodAllocationConfig>()?;
+    m.add_class::<config::AntConfig>()?;
+    m.add_class::<config::CombatConfig>()?;
+    m.add_class::<config::CasteConfig>()?;
+    m.add_class::<config::CasteDemand>()?;
+    m.add_class::<config::CastesConfig>()?;
+    m.add_class::<config::RecruitmentConfig>()?;
+    m.add_class::<config::EnergyConfig>()?;
+    m.add_class::<config::AnimationConfig>()?;
+    m.add_class::<environment::Environment>()?;
+    m.add_class::<ant::Ant>()?;
+    m.add_class::<colony::Queen>()?;
+    m.add_class::<colony::Colony>()?;
+    m.add_class::<simulation::Simulation>()?;
+    m.add_function(wrap_pyfunction!(batch::run_batch, m)?)?;
+    config_error::register(m)?;
     Ok(())
 }
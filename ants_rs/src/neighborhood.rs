@@ -0,0 +1,129 @@
+// Which cells count as adjacent under each `Neighborhood`, and how far apart
+// two cells are in steps. Hexagonal grids use axial coordinates: cell (x, y)
+// touches (x + 1, y - 1) and (x - 1, y + 1) besides the four straight
+// neighbours, so the square array holds a rhombus of hexagons.
+use crate::config::Neighborhood;
+
+// Same order as `possible_moves` in `Ant.random_move`
+const VON_NEUMANN_MOVES: [(i64, i64); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const MOORE_MOVES: [(i64, i64); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+const HEX_MOVES: [(i64, i64); 6] = [(-1, 0), (1, 0), (0, -1), (0, 1), (1, -1), (-1, 1)];
+
+impl Neighborhood {
+    // The cells an ant picks from when it moves on its own.
+    pub fn moves(self) -> &'static [(i64, i64)] {
+        match self {
+            Neighborhood::VonNeumann => &VON_NEUMANN_MOVES,
+            Neighborhood::Moore => &MOORE_MOVES,
+            Neighborhood::Hex => &HEX_MOVES,
+        }
+    }
+
+    // The steps `move_towards` can take. On the 4-neighbour grid that keeps
+    // the diagonal steps of `Ant.move_towards`, so both square grids home in
+    // with king moves; `Terrain::step` keeps them from cutting wall corners.
+    pub fn homing_moves(self) -> &'static [(i64, i64)] {
+        match self {
+            Neighborhood::VonNeumann | Neighborhood::Moore => &MOORE_MOVES,
+            Neighborhood::Hex => &HEX_MOVES,
+        }
+    }
+
+    // Steps needed to cover the offset (dx, dy): Manhattan, Chebyshev or hex
+    // distance. Perception radii are measured in the same unit.
+    pub fn norm(self, dx: i64, dy: i64) -> usize {
+        let norm = match self {
+            Neighborhood::VonNeumann => dx.abs() + dy.abs(),
+            Neighborhood::Moore => dx.abs().max(dy.abs()),
+            Neighborhood::Hex => (dx.abs() + dy.abs() + (dx + dy).abs()) / 2,
+        };
+        norm as usize
+    }
+
+    // The dy for which `norm(dx, dy)` is smallest, as an inclusive range;
+    // the norm only grows moving away from it either way.
+    pub fn nearest_rows(self, dx: i64) -> (i64, i64) {
+        match self {
+            Neighborhood::VonNeumann => (0, 0),
+            Neighborhood::Moore => (-dx.abs(), dx.abs()),
+            Neighborhood::Hex if dx >= 0 => (-dx, 0),
+            Neighborhood::Hex => (0, -dx),
+        }
+    }
+
+    // Greedy steps towards an offset of (dx, dy), best first, for
+    // `move_towards` to try in turn. The square grids step by the sign of
+    // each axis like `Ant.move_towards`, then fall back to either straight
+    // step; the hex grid tries every move that gets closer.
+    pub fn greedy_steps(self, dx: i64, dy: i64) -> Vec<(i64, i64)> {
+        match self {
+            Neighborhood::VonNeumann | Neighborhood::Moore => {
+                let (dx, dy) = (dx.signum(), dy.signum());
+                [(dx, dy), (dx, 0), (0, dy)]
+                    .into_iter()
+                    .filter(|&step| step != (0, 0))
+                    .collect()
+            }
+            Neighborhood::Hex => {
+                let distance = self.norm(dx, dy);
+                let mut steps: Vec<(i64, i64)> = HEX_MOVES
+                    .iter()
+                    .copied()
+                    .filter(|&(mx, my)| self.norm(dx - mx, dy - my) < distance)
+                    .collect();
+                steps.sort_by_key(|&(mx, my)| self.norm(dx - mx, dy - my));
+                steps
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neighbours(neighborhood: Neighborhood, (x, y): (i64, i64)) -> Vec<(i64, i64)> {
+        let mut cells: Vec<(i64, i64)> = neighborhood
+            .moves()
+            .iter()
+            .map(|&(dx, dy)| (x + dx, y + dy))
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    // Axial coordinates need no offset rows: a cell on an even row and one
+    // on an odd row have their six neighbours in the same places.
+    #[test]
+    fn hex_neighbours_are_the_same_on_even_and_odd_rows() {
+        assert_eq!(
+            neighbours(Neighborhood::Hex, (4, 4)),
+            [(3, 4), (3, 5), (4, 3), (4, 5), (5, 3), (5, 4)]
+        );
+        assert_eq!(
+            neighbours(Neighborhood::Hex, (4, 5)),
+            [(3, 5), (3, 6), (4, 4), (4, 6), (5, 4), (5, 5)]
+        );
+        // Exactly the cells one step away
+        for (x, y) in [(4, 4), (4, 5)] {
+            let mut ring = Vec::new();
+            for dx in -2..=2 {
+                for dy in -2..=2 {
+                    if Neighborhood::Hex.norm(dx, dy) == 1 {
+                        ring.push((x + dx, y + dy));
+                    }
+                }
+            }
+            assert_eq!(neighbours(Neighborhood::Hex, (x, y)), ring);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, UInt32Array, UInt64Array,
+};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::ant_store::AntStore;
+
+pub const DEFAULT_BATCH_SIZE: usize = 65_536;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    Ipc,
+    Parquet,
+}
+
+impl RecordFormat {
+    // An explicit "ipc"/"parquet" wins; otherwise `.parquet` files are Parquet
+    // and everything else is an Arrow IPC file.
+    pub fn resolve(format: Option<&str>, path: &Path) -> PyResult<RecordFormat> {
+        match format {
+            Some("ipc" | "arrow") => Ok(RecordFormat::Ipc),
+            Some("parquet") => Ok(RecordFormat::Parquet),
+            Some(other) => Err(PyValueError::new_err(format!(
+                "unknown trajectory format {other:?}, expected 'ipc' or 'parquet'"
+            ))),
+            None if path.extension().is_some_and(|ext| ext == "parquet") => {
+                Ok(RecordFormat::Parquet)
+            }
+            None => Ok(RecordFormat::Ipc),
+        }
+    }
+}
+
+enum Sink {
+    Ipc(FileWriter<BufWriter<File>>),
+    Parquet(ArrowWriter<File>),
+}
+
+// Streams one row per live ant per recorded step to disk. Rows are buffered
+// column-wise and written out as a record batch once `batch_size` of them
+// have piled up, so memory stays bounded however long the run is.
+pub struct TrajectoryRecorder {
+    path: PathBuf,
+    schema: SchemaRef,
+    sink: Option<Sink>,
+    batch_size: usize,
+    time: Vec<f64>,
+    id: Vec<u64>,
+    colony: Vec<u32>,
+    caste: Vec<&'static str>,
+    x: Vec<u32>,
+    y: Vec<u32>,
+    food: Vec<f64>,
+    returning_to_queen: Vec<bool>,
+    age: Vec<f64>,
+    energy: Vec<f64>,
+}
+
+impl TrajectoryRecorder {
+    pub fn create(path: &Path, format: RecordFormat, batch_size: usize) -> PyResult<Self> {
+        if batch_size == 0 {
+            return Err(PyValueError::new_err("batch_size must be positive"));
+        }
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("time", DataType::Float64, false),
+            Field::new("id", DataType::UInt64, false),
+            Field::new("colony", DataType::UInt32, false),
+            Field::new("caste", DataType::Utf8, false),
+            Field::new("x", DataType::UInt32, false),
+            Field::new("y", DataType::UInt32, false),
+            Field::new("food", DataType::Float64, false),
+            Field::new("returning_to_queen", DataType::Boolean, false),
+            Field::new("age", DataType::Float64, false),
+            Field::new("energy", DataType::Float64, false),
+        ]));
+        let file = File::create(path).map_err(|e| write_error(path, e))?;
+        let sink = match format {
+            RecordFormat::Ipc => Sink::Ipc(
+                FileWriter::try_new(BufWriter::new(file), &schema)
+                    .map_err(|e| write_error(path, e))?,
+            ),
+            RecordFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .set_max_row_group_size(batch_size)
+                    .build();
+                Sink::Parquet(
+                    ArrowWriter::try_new(file, schema.clone(), Some(properties))
+                        .map_err(|e| write_error(path, e))?,
+                )
+            }
+        };
+        Ok(TrajectoryRecorder {
+            path: path.to_path_buf(),
+            schema,
+            sink: Some(sink),
+            batch_size,
+            time: Vec::with_capacity(batch_size),
+            id: Vec::with_capacity(batch_size),
+            colony: Vec::with_capacity(batch_size),
+            caste: Vec::with_capacity(batch_size),
+            x: Vec::with_capacity(batch_size),
+            y: Vec::with_capacity(batch_size),
+            food: Vec::with_capacity(batch_size),
+            returning_to_queen: Vec::with_capacity(batch_size),
+            age: Vec::with_capacity(batch_size),
+            energy: Vec::with_capacity(batch_size),
+        })
+    }
+
+    // Appends the state of every live ant of one colony at `time`.
+    pub fn record(&mut self, time: f64, ants: &AntStore) -> PyResult<()> {
+        self.time.extend(std::iter::repeat_n(time, ants.len()));
+        self.id.extend_from_slice(&ants.ids);
+        self.colony
+            .extend(std::iter::repeat_n(u32::from(ants.colony), ants.len()));
+        self.caste
+            .extend(ants.castes.iter().map(|caste| caste.as_str()));
+        self.x.extend(ants.positions.iter().map(|&(x, _)| x as u32));
+        self.y.extend(ants.positions.iter().map(|&(_, y)| y as u32));
+        self.food.extend_from_slice(&ants.food);
+        self.returning_to_queen
+            .extend_from_slice(&ants.returning_to_queen);
+        self.age.extend_from_slice(&ants.ages);
+        self.energy.extend_from_slice(&ants.energies);
+        if self.time.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    // Writes the buffered rows out as one record batch.
+    pub fn flush(&mut self) -> PyResult<()> {
+        if self.time.is_empty() {
+            return Ok(());
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Float64Array::from(std::mem::take(&mut self.time))),
+            Arc::new(UInt64Array::from(std::mem::take(&mut self.id))),
+            Arc::new(UInt32Array::from(std::mem::take(&mut self.colony))),
+            Arc::new(StringArray::from(std::mem::take(&mut self.caste))),
+            Arc::new(UInt32Array::from(std::mem::take(&mut self.x))),
+            Arc::new(UInt32Array::from(std::mem::take(&mut self.y))),
+            Arc::new(Float64Array::from(std::mem::take(&mut self.food))),
+            Arc::new(BooleanArray::from(std::mem::take(
+                &mut self.returning_to_queen,
+            ))),
+            Arc::new(Float64Array::from(std::mem::take(&mut self.age))),
+            Arc::new(Float64Array::from(std::mem::take(&mut self.energy))),
+        ];
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)
+            .map_err(|e| write_error(&self.path, e))?;
+        match self.sink.as_mut() {
+            Some(Sink::Ipc(writer)) => writer.write(&batch).map_err(|e| write_error(&self.path, e)),
+            Some(Sink::Parquet(writer)) => {
+                writer.write(&batch).map_err(|e| write_error(&self.path, e))
+            }
+            None => Err(PyValueError::new_err(format!(
+                "{}: trajectory recorder is already closed",
+                self.path.display()
+            ))),
+        }
+    }
+
+    // Flushes the remaining rows and writes the file footer. The file is only
+    // readable once this has run; dropping the recorder calls it too.
+    pub fn finish(&mut self) -> PyResult<()> {
+        if self.sink.is_none() {
+            return Ok(());
+        }
+        self.flush()?;
+        match self.sink.take() {
+            Some(Sink::Ipc(mut writer)) => writer.finish().map_err(|e| write_error(&self.path, e)),
+            Some(Sink::Parquet(writer)) => writer
+                .close()
+                .map(|_| ())
+                .map_err(|e| write_error(&self.path, e)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TrajectoryRecorder {
+    fn drop(&mut self) {
+        // Errors can't be reported from here; call `finish` to see them
+        let _ = self.finish();
+    }
+}
+
+fn write_error(path: &Path, error: impl std::fmt::Display) -> PyErr {
+    PyIOError::new_err(format!("{}: {error}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use arrow_ipc::reader::FileReader;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::ant::Ant;
+
+    #[test]
+    fn recordings_read_back_with_every_row() {
+        let mut ants = AntStore::with_capacity(1, 3);
+        for x in 0..3 {
+            ants.insert(Ant::new((x, 2), 0, 100.0, 10.0)).unwrap();
+        }
+        ants.returning_to_queen[1] = true;
+
+        for format in [RecordFormat::Ipc, RecordFormat::Parquet] {
+            let path = std::env::temp_dir().join(format!(
+                "ants_rs_recording_{}_{format:?}",
+                std::process::id()
+            ));
+            // A batch size that doesn't divide the row count, so the last
+            // batch is a partial one written by `finish`
+            let mut recorder = TrajectoryRecorder::create(&path, format, 4).unwrap();
+            for step in 0..5 {
+                recorder.record(step as f64 * 0.1, &ants).unwrap();
+            }
+            recorder.finish().unwrap();
+
+            let file = File::open(&path).unwrap();
+            let (schema, batches) = match format {
+                RecordFormat::Ipc => {
+                    let reader = FileReader::try_new(file, None).unwrap();
+                    (reader.schema(), reader.collect::<Result<Vec<_>, _>>())
+                }
+                RecordFormat::Parquet => {
+                    let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+                    let schema = builder.schema().clone();
+                    let reader = builder.build().unwrap();
+                    (schema, reader.collect::<Result<Vec<_>, _>>())
+                }
+            };
+            let batches = batches.unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(schema.fields(), recorder.schema.fields(), "{format:?}");
+            let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+            assert_eq!(rows, 5 * ants.len(), "{format:?}");
+            let returning = batches
+                .iter()
+                .flat_map(|batch| {
+                    batch
+                        .column_by_name("returning_to_queen")
+                        .unwrap()
+                        .as_any()
+                        .downcast_ref::<BooleanArray>()
+                        .unwrap()
+                        .values()
+                        .iter()
+                        .collect::<Vec<_>>()
+                })
+                .filter(|&returning| returning)
+                .count();
+            assert_eq!(returning, 5, "{format:?}");
+        }
+    }
+}
@@ -0,0 +1,392 @@
+// Draws the simulation the way `Simulation.animate` in `simulation.py` lays
+// out its matplotlib figure, straight into RGBA pixels, and encodes frames as
+// PNG files, an animated GIF or an APNG without matplotlib or a display.
+use std::borrow::Borrow;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::colony::Colony;
+use crate::config::SimulationConfig;
+use crate::environment::{Environment, Layer, Position};
+
+pub const DEFAULT_CELL_SIZE: u32 = 4;
+
+// Scatter marker sizes are in points squared; this is roughly how many points
+// one cell spans in the default 8x6 inch figure of a 200x200 grid.
+const POINTS_PER_CELL: f32 = 3.0;
+const ANT_MARKER_SIZE: f32 = 20.0;
+const QUEEN_MARKER_SIZE: f32 = 100.0;
+// `food_scatter` sizes are the food amount times this
+const FOOD_MARKER_SCALE: f32 = 5.0;
+
+pub(crate) const WHITE: [f32; 3] = [1.0, 1.0, 1.0];
+pub(crate) const BLACK: [f32; 3] = [0.0, 0.0, 0.0];
+pub(crate) const RED: [f32; 3] = [1.0, 0.0, 0.0];
+// Matplotlib's "green" is #008000
+pub(crate) const GREEN: [f32; 3] = [0.0, 128.0 / 255.0, 0.0];
+pub(crate) const WALL: [f32; 3] = [0.35, 0.35, 0.35];
+// Ants and queens of the second colony onwards, from matplotlib's "tab10"
+// minus the red and green already taken
+const RIVAL_COLORS: [[f32; 3]; 8] = [
+    [
+        0x1f as f32 / 255.0,
+        0x77 as f32 / 255.0,
+        0xb4 as f32 / 255.0,
+    ],
+    [
+        0xff as f32 / 255.0,
+        0x7f as f32 / 255.0,
+        0x0e as f32 / 255.0,
+    ],
+    [
+        0x94 as f32 / 255.0,
+        0x67 as f32 / 255.0,
+        0xbd as f32 / 255.0,
+    ],
+    [
+        0x8c as f32 / 255.0,
+        0x56 as f32 / 255.0,
+        0x4b as f32 / 255.0,
+    ],
+    [
+        0xe3 as f32 / 255.0,
+        0x77 as f32 / 255.0,
+        0xc2 as f32 / 255.0,
+    ],
+    [
+        0x7f as f32 / 255.0,
+        0x7f as f32 / 255.0,
+        0x7f as f32 / 255.0,
+    ],
+    [
+        0xbc as f32 / 255.0,
+        0xbd as f32 / 255.0,
+        0x22 as f32 / 255.0,
+    ],
+    [
+        0x17 as f32 / 255.0,
+        0xbe as f32 / 255.0,
+        0xcf as f32 / 255.0,
+    ],
+];
+
+// An opaque RGBA image, rows from the top, 8 bits per channel.
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+// One frame of the current state, `cell_size` pixels per grid cell. Like the
+// plot (`origin="lower"`), y grows upwards.
+pub fn render<C: Borrow<Colony>>(
+    environment: &Environment,
+    colonies: &[C],
+    cell_size: u32,
+) -> Frame {
+    let config = &environment.config;
+    let (grid_width, grid_height) = config.grid_size;
+    let mut canvas = Canvas::new(
+        grid_width as u32 * cell_size,
+        grid_height as u32 * cell_size,
+    );
+
+    for x in 0..grid_width {
+        for y in 0..grid_height {
+            if environment.terrain.is_wall((x, y)) {
+                canvas.fill_cell(x, y, cell_size, WALL, 1.0);
+                continue;
+            }
+            let (color, alpha) = pheromone_rgba(environment, (x, y));
+            if alpha > 0.0 {
+                canvas.fill_cell(x, y, cell_size, color, alpha);
+            }
+        }
+    }
+
+    // Markers, in the order the scatters are created
+    for colony in colonies {
+        let colony = colony.borrow();
+        let (ant_color, _) = colony_colors(colony.id);
+        for &position in &colony.ants.positions {
+            canvas.disc(position, cell_size, ANT_MARKER_SIZE, ant_color, 1.0);
+        }
+    }
+    for colony in colonies {
+        let colony = colony.borrow();
+        let (_, queen_color) = colony_colors(colony.id);
+        canvas.disc(
+            colony.queen.position,
+            cell_size,
+            QUEEN_MARKER_SIZE,
+            queen_color,
+            1.0,
+        );
+    }
+    for position in environment.food_positions.iter() {
+        let size = environment.food_amount(position) * FOOD_MARKER_SCALE;
+        canvas.disc(position, cell_size, size, GREEN, 0.5);
+    }
+
+    canvas.into_frame()
+}
+
+// Ant and queen colors: black and red for the first colony, as in the plot,
+// then one color per rival colony.
+pub(crate) fn colony_colors(colony: usize) -> ([f32; 3], [f32; 3]) {
+    match colony {
+        0 => (BLACK, RED),
+        _ => {
+            let color = RIVAL_COLORS[(colony - 1) % RIVAL_COLORS.len()];
+            (color, color)
+        }
+    }
+}
+
+// A cell's pheromones as one RGBA value, like a pixel of
+// `get_combined_pheromone_grid`: rich in red, food in green, regular in blue,
+// and the strongest of them as alpha. Each layer shows the strongest
+// colony's level.
+pub(crate) fn pheromone_rgba(environment: &Environment, position: Position) -> ([f32; 3], f32) {
+    let config = &environment.config;
+    let max_intensity = config.pheromone_initial_intensity as f32;
+    let max_opacity = config.pheromone_max_opacity as f32;
+    let opacity = |layer: Layer| {
+        let level = (0..config.num_colonies)
+            .map(|colony| environment.level(position, colony, layer))
+            .fold(0.0, f32::max);
+        (level / max_intensity * max_opacity).clamp(0.0, max_opacity)
+    };
+    let (regular, food, rich) = (
+        opacity(Layer::Regular),
+        opacity(Layer::FoodPheromone),
+        opacity(Layer::Rich),
+    );
+    ([rich, food, regular], regular.max(food).max(rich))
+}
+
+pub(crate) fn blend(under: [f32; 3], color: [f32; 3], alpha: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| color[i] * alpha + under[i] * (1.0 - alpha))
+}
+
+pub(crate) fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 3]>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![WHITE; (width * height) as usize],
+        }
+    }
+
+    fn blend(&mut self, px: u32, py: u32, color: [f32; 3], alpha: f32) {
+        let pixel = &mut self.pixels[(py * self.width + px) as usize];
+        *pixel = blend(*pixel, color, alpha);
+    }
+
+    fn fill_cell(&mut self, x: usize, y: usize, cell_size: u32, color: [f32; 3], alpha: f32) {
+        let left = x as u32 * cell_size;
+        let top = self.height - (y as u32 + 1) * cell_size;
+        for py in top..top + cell_size {
+            for px in left..left + cell_size {
+                self.blend(px, py, color, alpha);
+            }
+        }
+    }
+
+    // A scatter marker of `size` points squared centred on a cell.
+    fn disc(&mut self, position: Position, cell_size: u32, size: f32, color: [f32; 3], alpha: f32) {
+        let radius = (size.max(0.0).sqrt() / 2.0 / POINTS_PER_CELL * cell_size as f32).max(0.5);
+        let cx = (position.0 as f32 + 0.5) * cell_size as f32;
+        let cy = self.height as f32 - (position.1 as f32 + 0.5) * cell_size as f32;
+        let min_x = (cx - radius).floor().max(0.0) as u32;
+        let max_x = ((cx + radius).ceil() as u32).min(self.width);
+        let min_y = (cy - radius).floor().max(0.0) as u32;
+        let max_y = ((cy + radius).ceil() as u32).min(self.height);
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let dx = px as f32 + 0.5 - cx;
+                let dy = py as f32 + 0.5 - cy;
+                if dx * dx + dy * dy <= radius * radius {
+                    self.blend(px, py, color, alpha);
+                }
+            }
+        }
+    }
+
+    fn into_frame(self) -> Frame {
+        let rgba = self
+            .pixels
+            .iter()
+            .flat_map(|&[r, g, b]| [to_byte(r), to_byte(g), to_byte(b), 255])
+            .collect();
+        Frame {
+            width: self.width,
+            height: self.height,
+            rgba,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationFormat {
+    // `frame_00000.png`, `frame_00001.png`, ... in a directory
+    Frames,
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    // An explicit "frames"/"gif"/"apng" wins; otherwise `.gif` files are GIFs,
+    // `.png`/`.apng` files are APNGs and anything else is a frame directory.
+    pub fn resolve(format: Option<&str>, path: &Path) -> Option<AnimationFormat> {
+        match format {
+            Some("frames") => Some(AnimationFormat::Frames),
+            Some("gif") => Some(AnimationFormat::Gif),
+            Some("apng") => Some(AnimationFormat::Apng),
+            Some(_) => None,
+            None => match path.extension().and_then(|ext| ext.to_str()) {
+                Some("gif") => Some(AnimationFormat::Gif),
+                Some("png" | "apng") => Some(AnimationFormat::Apng),
+                _ => Some(AnimationFormat::Frames),
+            },
+        }
+    }
+}
+
+enum Sink {
+    Frames { dir: PathBuf, next: usize },
+    Gif(gif::Encoder<BufWriter<File>>),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+// Encodes a fixed number of equally sized frames. APNG needs the frame count
+// up front, so it is fixed for every format.
+pub struct AnimationWriter {
+    sink: Sink,
+    fps: u32,
+    width: u32,
+    height: u32,
+}
+
+impl AnimationWriter {
+    pub fn create(
+        path: &Path,
+        format: AnimationFormat,
+        width: u32,
+        height: u32,
+        fps: u32,
+        num_frames: u32,
+    ) -> io::Result<AnimationWriter> {
+        let sink = match format {
+            AnimationFormat::Frames => {
+                fs::create_dir_all(path)?;
+                Sink::Frames {
+                    dir: path.to_path_buf(),
+                    next: 0,
+                }
+            }
+            AnimationFormat::Gif => {
+                let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height))
+                else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{width}x{height} is too large for a GIF"),
+                    ));
+                };
+                let file = BufWriter::new(File::create(path)?);
+                let mut encoder = gif::Encoder::new(file, gif_width, gif_height, &[])
+                    .map_err(io::Error::other)?;
+                encoder
+                    .set_repeat(gif::Repeat::Infinite)
+                    .map_err(io::Error::other)?;
+                Sink::Gif(encoder)
+            }
+            AnimationFormat::Apng => {
+                let file = BufWriter::new(File::create(path)?);
+                let mut encoder = png::Encoder::new(file, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder
+                    .set_animated(num_frames.max(1), 0)
+                    .map_err(io::Error::other)?;
+                encoder
+                    .set_frame_delay(1, fps as u16)
+                    .map_err(io::Error::other)?;
+                Sink::Apng(encoder.write_header().map_err(io::Error::other)?)
+            }
+        };
+        Ok(AnimationWriter {
+            sink,
+            fps,
+            width,
+            height,
+        })
+    }
+
+    pub fn push(&mut self, frame: &Frame) -> io::Result<()> {
+        if (frame.width, frame.height) != (self.width, self.height) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "all frames of an animation must have the same size",
+            ));
+        }
+        match &mut self.sink {
+            Sink::Frames { dir, next } => {
+                write_png(&dir.join(format!("frame_{next:05}.png")), frame)?;
+                *next += 1;
+                Ok(())
+            }
+            Sink::Gif(encoder) => {
+                let mut rgba = frame.rgba.clone();
+                let mut gif_frame = gif::Frame::from_rgba_speed(
+                    frame.width as u16,
+                    frame.height as u16,
+                    &mut rgba,
+                    10,
+                );
+                // In hundredths of a second
+                gif_frame.delay = (100 / self.fps.max(1)).max(1) as u16;
+                encoder.write_frame(&gif_frame).map_err(io::Error::other)
+            }
+            Sink::Apng(writer) => writer
+                .write_image_data(&frame.rgba)
+                .map_err(io::Error::other),
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self.sink {
+            Sink::Frames { .. } => Ok(()),
+            Sink::Gif(encoder) => encoder.into_inner().map(|_| ()),
+            Sink::Apng(writer) => writer.finish().map_err(io::Error::other),
+        }
+    }
+}
+
+pub fn write_png(path: &Path, frame: &Frame) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, frame.width, frame.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(&frame.rgba)
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+// Number of frames `Simulation.animate` draws for a full run.
+pub fn default_frame_count(config: &SimulationConfig) -> u32 {
+    (config.simulation_duration / config.animation.step_interval) as u32
+}
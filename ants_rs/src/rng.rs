@@ -0,0 +1,30 @@
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// ChaCha8 output is fixed by its spec, unlike `StdRng`, so a seed replays the
+// same trajectory across platforms and crate versions.
+pub type SimRng = ChaCha8Rng;
+
+// Independent streams carved out of one simulation seed, so adding draws in
+// one component never shifts the sequence seen by another.
+pub const POPULATE_STREAM: u64 = 0;
+pub const ENVIRONMENT_STREAM: u64 = 1;
+pub const COLONY_STREAM: u64 = 2;
+pub const COMBAT_STREAM: u64 = 3;
+
+// The first colony keeps `COLONY_STREAM`; the others get streams well clear
+// of the shared ones.
+pub fn colony_stream(colony: usize) -> u64 {
+    COLONY_STREAM + ((colony as u64) << 32)
+}
+
+pub fn stream(seed: u64, stream: u64) -> SimRng {
+    let mut rng = SimRng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+// Picks a seed when the caller didn't, so the run can still be replayed.
+pub fn resolve_seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| rand::thread_rng().next_u64())
+}
@@ -0,0 +1,211 @@
+// Loads a `SimulationConfig` the way pydantic-settings builds the Python
+// one: from a file, then `ANTS_` environment variables on top, then
+// validation.
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::config::SimulationConfig;
+use crate::config_error::ConfigError;
+
+pub const ENV_PREFIX: &str = "ANTS_";
+// Same as `env_nested_delimiter` in `config.SimulationConfig`, so
+// `ANTS_FOOD__SPAWN_CHANCE` sets `food.spawn_chance`.
+pub const ENV_NESTED_DELIMITER: &str = "__";
+
+// Defaults, or the file at `path` if given, with the process environment
+// applied on top and the result validated.
+pub fn load(path: Option<&Path>) -> Result<SimulationConfig, ConfigError> {
+    let config = match path {
+        Some(path) => from_file(path)?,
+        None => SimulationConfig::default(),
+    };
+    let vars = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    let config = apply_env(config, vars)?;
+    config.validate()?;
+    Ok(config)
+}
+
+// Reads a TOML, YAML or JSON file, picked by extension. Missing fields keep
+// their defaults and unknown ones, like a dumped `perception_radius`, are
+// ignored.
+pub fn from_file(path: &Path) -> Result<SimulationConfig, ConfigError> {
+    let text = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let parse_error = |message: String| ConfigError::Parse {
+        path: path.to_path_buf(),
+        message,
+    };
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|e| parse_error(e.to_string())),
+        Some("yaml" | "yml") => serde_yaml::from_str(&text).map_err(|e| parse_error(e.to_string())),
+        Some("json") => serde_json::from_str(&text).map_err(|e| parse_error(e.to_string())),
+        _ => Err(parse_error(
+            "unsupported config format, expected .toml, .yaml, .yml or .json".to_string(),
+        )),
+    }
+}
+
+// Overrides fields from `ANTS_`-prefixed variables. Like pydantic-settings,
+// names are case-insensitive, values are parsed as JSON when they can be
+// (so `ANTS_GRID_SIZE=[100,80]` works) and names that don't match a field are
+// ignored. Shallower names go first, so `ANTS_FOOD__SPAWN_CHANCE` wins over
+// the same key inside a JSON `ANTS_FOOD`.
+pub fn apply_env<I>(config: SimulationConfig, vars: I) -> Result<SimulationConfig, ConfigError>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut overrides: Vec<(String, Vec<String>, String)> = vars
+        .into_iter()
+        .filter_map(|(var, raw)| {
+            let name = var.to_ascii_lowercase();
+            let keys = name
+                .strip_prefix(&ENV_PREFIX.to_ascii_lowercase())?
+                .split(ENV_NESTED_DELIMITER)
+                .map(str::to_string)
+                .collect();
+            Some((var, keys, raw))
+        })
+        .collect();
+    overrides.sort_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let mut value = serde_json::to_value(&config).expect("config serializes to JSON");
+    let mut config = config;
+    for (var, keys, raw) in overrides {
+        let Some((last, parents)) = keys.split_last() else {
+            continue;
+        };
+        let target = parents
+            .iter()
+            .try_fold(&mut value, |target, key| target.get_mut(key.as_str()));
+        let Some(fields) = target.and_then(Value::as_object_mut) else {
+            continue;
+        };
+        if !fields.contains_key(last.as_str()) {
+            continue;
+        }
+        fields.insert(last.clone(), parse_env_value(&raw));
+        // Deserialize after every override so errors name the variable
+        config = serde_json::from_value(value.clone()).map_err(|e| ConfigError::Env {
+            var,
+            message: e.to_string(),
+        })?;
+    }
+    Ok(config)
+}
+
+fn parse_env_value(raw: &str) -> Value {
+    serde_json::from_str(raw)
+        .or_else(|_| serde_json::from_str(&raw.to_ascii_lowercase()))
+        .unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+// The config as `model_dump()` would give it, computed `perception_radius`
+// included.
+pub fn to_value(config: &SimulationConfig) -> Value {
+    let mut value = serde_json::to_value(config).expect("config serializes to JSON");
+    value["perception_radius"] = config.perception_radius().into();
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    // Writes `text` to a temporary file named `name` and returns its path.
+    fn config_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ants_rs_{}_{name}", std::process::id()));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|&(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn toml_and_yaml_files_set_the_same_fields() {
+        let files = [
+            (
+                "config.toml",
+                "grid_size = [40, 30]\nnum_ants = 12\n\n[food]\nspawn_chance = 0.25\n",
+            ),
+            (
+                "config.yaml",
+                "grid_size: [40, 30]\nnum_ants: 12\nfood:\n  spawn_chance: 0.25\n",
+            ),
+        ];
+        for (name, text) in files {
+            let path = config_file(name, text);
+            let config = from_file(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(config.grid_size, (40, 30), "{name}");
+            assert_eq!(config.num_ants, 12, "{name}");
+            assert_eq!(config.food.spawn_chance, 0.25, "{name}");
+            // Fields the file leaves out keep their defaults
+            let defaults = SimulationConfig::default();
+            assert_eq!(
+                config.food.spawn_baseline, defaults.food.spawn_baseline,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn env_vars_override_the_file() {
+        let path = config_file(
+            "env.toml",
+            "num_ants = 12\n\n[food]\nspawn_chance = 0.25\nspawn_baseline = 4\n",
+        );
+        let config = from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let config = apply_env(
+            config,
+            vars(&[
+                ("ants_num_ants", "20"),
+                ("ANTS_FOOD__SPAWN_CHANCE", "0.5"),
+                ("ANTS_FOOD", r#"{"spawn_chance": 0.9, "spawn_baseline": 6}"#),
+                ("OTHER_NUM_ANTS", "99"),
+                ("ANTS_NOT_A_FIELD", "1"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.num_ants, 20);
+        // The nested name wins over the same key in `ANTS_FOOD`...
+        assert_eq!(config.food.spawn_chance, 0.5);
+        // ...which still sets the keys it alone names
+        assert_eq!(config.food.spawn_baseline, 6);
+    }
+
+    #[test]
+    fn bad_values_are_config_errors() {
+        let error = apply_env(
+            SimulationConfig::default(),
+            vars(&[("ANTS_NUM_ANTS", "lots")]),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&error, ConfigError::Env { var, .. } if var == "ANTS_NUM_ANTS"),
+            "{error}"
+        );
+        assert_eq!(error.field(), None);
+
+        let path = config_file("bad.yaml", "num_ants: lots\n");
+        let error = from_file(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(error, ConfigError::Parse { .. }), "{error}");
+        assert_eq!(error.field(), None);
+
+        let error = from_file(Path::new("/nonexistent/ants.toml")).unwrap_err();
+        assert!(matches!(error, ConfigError::Io { .. }), "{error}");
+        assert_eq!(error.field(), None);
+    }
+}
@@ -0,0 +1,600 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::f64::consts::TAU;
+use std::ops::RangeInclusive;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+
+#[cfg(feature = "python")]
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyDict, PyType},
+};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::ant::Ant;
+#[cfg(feature = "python")]
+use crate::colony::update_colonies;
+use crate::colony::{Colony, Queen};
+use crate::config::{Caste, SimulationConfig};
+use crate::config_error::ConfigError;
+use crate::environment::{Environment, Position};
+#[cfg(feature = "python")]
+use crate::recorder::{RecordFormat, TrajectoryRecorder, DEFAULT_BATCH_SIZE};
+#[cfg(feature = "python")]
+use crate::render::{self, AnimationFormat, AnimationWriter, DEFAULT_CELL_SIZE};
+use crate::rng;
+#[cfg(feature = "python")]
+use crate::snapshot::{Snapshot, SnapshotFormat, SnapshotRef};
+use crate::terrain::Terrain;
+#[cfg(feature = "python")]
+use crate::viewer::{self, ViewOptions, ViewSource};
+
+const MAX_PLACEMENT_DRAWS: usize = 1000;
+
+// Mirrors `simulation.Simulation`, minus the matplotlib code.
+#[cfg(feature = "python")]
+#[pyclass(subclass)]
+pub struct Simulation {
+    pub config: SimulationConfig,
+    pub environment: Py<Environment>,
+    pub colonies: Vec<Py<Colony>>,
+    pub ant_paths: BTreeMap<u64, Vec<Position>>,
+    // When false, `step` leaves `ant_paths` alone; useful while a recorder
+    // keeps the full history on disk instead.
+    pub track_paths: bool,
+    pub recorder: Option<TrajectoryRecorder>,
+    pub current_time: f64,
+    pub seed: u64,
+}
+
+// The scalar part of `get_stats`, cheap enough to collect from every run of a
+// batch. The top-level counts add up every colony; `queen_position` is the
+// first colony's, as with a single colony.
+#[derive(Clone, Debug, Serialize)]
+pub struct RunStats {
+    pub ants: usize,
+    pub eggs: usize,
+    pub food: f64,
+    pub queen_position: Position,
+    pub current_time: f64,
+    pub colonies: Vec<ColonyStats>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ColonyStats {
+    pub ants: usize,
+    pub eggs: usize,
+    pub food: f64,
+    pub food_collected: f64,
+    pub casualties: usize,
+    pub recruits: usize,
+    pub starved: usize,
+    pub food_consumed: f64,
+    // Live ants per caste, by name
+    pub castes: BTreeMap<&'static str, usize>,
+    pub queen_position: Position,
+}
+
+impl RunStats {
+    pub fn new<C: Borrow<Colony>>(colonies: &[C], current_time: f64) -> RunStats {
+        let colonies: Vec<ColonyStats> = colonies
+            .iter()
+            .map(|colony| {
+                let colony = colony.borrow();
+                ColonyStats {
+                    ants: colony.ants.len(),
+                    eggs: colony.eggs,
+                    food: colony.food_store,
+                    food_collected: colony.food_collected,
+                    casualties: colony.casualties,
+                    recruits: colony.recruits,
+                    starved: colony.starved,
+                    food_consumed: colony.food_consumed,
+                    castes: Caste::ALL
+                        .iter()
+                        .map(|caste| caste.as_str())
+                        .zip(colony.caste_counts())
+                        .collect(),
+                    queen_position: colony.queen.position,
+                }
+            })
+            .collect();
+        RunStats {
+            ants: colonies.iter().map(|colony| colony.ants).sum(),
+            eggs: colonies.iter().map(|colony| colony.eggs).sum(),
+            food: colonies.iter().map(|colony| colony.food).sum(),
+            queen_position: colonies[0].queen_position,
+            current_time,
+            colonies,
+        }
+    }
+
+    #[cfg(feature = "python")]
+    pub fn into_py_dict(self, py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+        let stats = PyDict::new_bound(py);
+        stats.set_item("ants", self.ants)?;
+        stats.set_item("eggs", self.eggs)?;
+        stats.set_item("food", self.food)?;
+        stats.set_item("queen_position", self.queen_position)?;
+        stats.set_item("current_time", self.current_time)?;
+        let colonies = self
+            .colonies
+            .into_iter()
+            .map(|colony| {
+                let stats = PyDict::new_bound(py);
+                stats.set_item("ants", colony.ants)?;
+                stats.set_item("eggs", colony.eggs)?;
+                stats.set_item("food", colony.food)?;
+                stats.set_item("food_collected", colony.food_collected)?;
+                stats.set_item("casualties", colony.casualties)?;
+                stats.set_item("recruits", colony.recruits)?;
+                stats.set_item("starved", colony.starved)?;
+                stats.set_item("food_consumed", colony.food_consumed)?;
+                stats.set_item("castes", colony.castes)?;
+                stats.set_item("queen_position", colony.queen_position)?;
+                Ok(stats)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        stats.set_item("colonies", colonies)?;
+        Ok(stats)
+    }
+}
+
+// Places the queen near the middle of the grid and scatters the initial ants,
+// as `Simulation.from_config_or_default` does, loading `terrain_map` if set.
+// With several colonies, each gets its own queen and `num_ants` ants.
+// Everything random downstream is drawn from streams of `seed`.
+pub fn populate(
+    config: &SimulationConfig,
+    seed: u64,
+) -> Result<(Environment, Vec<Colony>), ConfigError> {
+    let terrain = Terrain::for_config(config)?;
+    let mut rng = rng::stream(seed, rng::POPULATE_STREAM);
+    let (grid_width, grid_height) = config.grid_size;
+    let colonies = (0..config.num_colonies)
+        .map(|id| {
+            let (xs, ys) = nest_area(config, id);
+            let queen_position = place(&mut rng, &terrain, xs, ys);
+            let ants = (0..config.num_ants)
+                .map(|i| {
+                    Ant::new(
+                        place(&mut rng, &terrain, 0..=grid_width - 1, 0..=grid_height - 1),
+                        i as u64,
+                        config.ant.initial_lifespan,
+                        config.ant.carrying_capacity,
+                    )
+                })
+                .collect();
+            Colony::new(
+                config.clone(),
+                id,
+                Queen {
+                    position: queen_position,
+                },
+                ants,
+                seed,
+            )
+            .expect("num_ants is validated")
+        })
+        .collect();
+    let environment = Environment::new(config.clone(), seed, terrain);
+    Ok((environment, colonies))
+}
+
+// Where colony `id`'s queen may start. A lone queen starts in the middle half
+// of the grid like in Python; rival queens are spread evenly around an
+// ellipse halfway between the centre and the edges, each within an eighth of
+// the grid of its spot.
+fn nest_area(
+    config: &SimulationConfig,
+    id: usize,
+) -> (RangeInclusive<usize>, RangeInclusive<usize>) {
+    let (grid_width, grid_height) = config.grid_size;
+    if config.num_colonies == 1 {
+        return (
+            grid_width / 4..=3 * grid_width / 4,
+            grid_height / 4..=3 * grid_height / 4,
+        );
+    }
+    let angle = TAU * id as f64 / config.num_colonies as f64;
+    let around = |center: f64, size: usize| {
+        let (center, spread) = (center as usize, size / 8);
+        center.saturating_sub(spread)..=(center + spread).min(size - 1)
+    };
+    (
+        around(
+            (1.0 + angle.cos() / 2.0) * grid_width as f64 / 2.0,
+            grid_width,
+        ),
+        around(
+            (1.0 + angle.sin() / 2.0) * grid_height as f64 / 2.0,
+            grid_height,
+        ),
+    )
+}
+
+// A random open cell in the given ranges. Walls are redrawn a bounded number
+// of times before falling back to any open cell on the grid, in case the
+// ranges are all wall.
+fn place<R: Rng>(
+    rng: &mut R,
+    terrain: &Terrain,
+    xs: RangeInclusive<usize>,
+    ys: RangeInclusive<usize>,
+) -> Position {
+    for _ in 0..MAX_PLACEMENT_DRAWS {
+        let position = (rng.gen_range(xs.clone()), rng.gen_range(ys.clone()));
+        if terrain.is_open(position) {
+            return position;
+        }
+    }
+    let open_cells: Vec<Position> = terrain.open_cells().collect();
+    open_cells[rng.gen_range(0..open_cells.len())]
+}
+
+#[cfg(feature = "python")]
+impl Simulation {
+    pub fn new(py: Python<'_>, config: SimulationConfig, seed: u64) -> PyResult<Simulation> {
+        let (environment, colonies) = populate(&config, seed)?;
+        let ant_paths = colonies
+            .iter()
+            .flat_map(|colony| &colony.ants.ids)
+            .map(|&id| (id, Vec::new()))
+            .collect();
+        Ok(Simulation {
+            config,
+            environment: Py::new(py, environment)?,
+            colonies: colonies
+                .into_iter()
+                .map(|colony| Py::new(py, colony))
+                .collect::<PyResult<_>>()?,
+            ant_paths,
+            track_paths: true,
+            recorder: None,
+            current_time: 0.0,
+            seed,
+        })
+    }
+
+    pub fn step(&mut self, py: Python<'_>, time_delta: f64) -> PyResult<()> {
+        self.current_time += time_delta;
+        let mut environment = self.environment.borrow_mut(py);
+        let mut colonies: Vec<PyRefMut<'_, Colony>> = self
+            .colonies
+            .iter()
+            .map(|colony| colony.borrow_mut(py))
+            .collect();
+
+        environment.update(self.current_time);
+        {
+            let mut colonies: Vec<&mut Colony> =
+                colonies.iter_mut().map(|colony| &mut **colony).collect();
+            update_colonies(&mut environment, &mut colonies, time_delta);
+        }
+        for colony in colonies.iter_mut() {
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.record(self.current_time, &colony.ants)?;
+            }
+            if self.track_paths {
+                for (&id, &position) in colony.ants.ids.iter().zip(&colony.ants.positions) {
+                    self.ant_paths.entry(id).or_default().push(position);
+                }
+            }
+            colony.update(time_delta);
+        }
+
+        // Remove paths of dead ants
+        self.ant_paths
+            .retain(|&id, _| colonies.iter().any(|colony| colony.ants.contains(id)));
+        Ok(())
+    }
+
+    // Resumes the saved state as is: nothing is populated or read from
+    // `terrain_map`, as the snapshot already holds the terrain.
+    fn from_snapshot(py: Python<'_>, snapshot: Snapshot) -> PyResult<Simulation> {
+        Ok(Simulation {
+            config: snapshot.config,
+            environment: Py::new(py, snapshot.environment)?,
+            colonies: snapshot
+                .colonies
+                .into_iter()
+                .map(|colony| Py::new(py, colony))
+                .collect::<PyResult<_>>()?,
+            ant_paths: snapshot.ant_paths,
+            track_paths: true,
+            recorder: None,
+            current_time: snapshot.current_time,
+            seed: snapshot.seed,
+        })
+    }
+
+    // Runs `f` with every colony borrowed.
+    fn with_colonies<R>(&self, py: Python<'_>, f: impl FnOnce(&[&Colony]) -> R) -> R {
+        let colonies: Vec<PyRef<'_, Colony>> = self
+            .colonies
+            .iter()
+            .map(|colony| colony.borrow(py))
+            .collect();
+        let colonies: Vec<&Colony> = colonies.iter().map(|colony| &**colony).collect();
+        f(&colonies)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Simulation {
+    #[new]
+    #[pyo3(signature = (config=None, seed=None))]
+    fn py_new(
+        py: Python<'_>,
+        config: Option<&Bound<'_, PyAny>>,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let config = match config {
+            Some(config) => SimulationConfig::from_py(config)?,
+            None => SimulationConfig::default(),
+        };
+        Simulation::new(py, config, rng::resolve_seed(seed))
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (config=None, seed=None))]
+    fn from_config_or_default(
+        cls: &Bound<'_, PyType>,
+        config: Option<&Bound<'_, PyAny>>,
+        seed: Option<u64>,
+    ) -> PyResult<PyObject> {
+        Ok(cls.call1((config, seed))?.unbind())
+    }
+
+    // Writes a checkpoint that `load` resumes bit-exactly. `format` is
+    // "binary" (bincode) or "json"; by default `.json` paths get JSON.
+    // An active trajectory recording is not part of the checkpoint.
+    #[pyo3(signature = (path, format=None))]
+    fn save(&self, py: Python<'_>, path: PathBuf, format: Option<&str>) -> PyResult<()> {
+        let format = SnapshotFormat::resolve(format, &path)?;
+        let environment = self.environment.borrow(py);
+        self.with_colonies(py, |colonies| {
+            SnapshotRef {
+                config: &self.config,
+                environment: &environment,
+                colonies,
+                ant_paths: &self.ant_paths,
+                current_time: self.current_time,
+                seed: self.seed,
+            }
+            .write(&path, format)
+        })?;
+        Ok(())
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (path, format=None))]
+    // Builds a `Simulation` straight from the snapshot, without running
+    // `__init__`. Subclasses are turned away, since what comes back would
+    // not be an instance of them.
+    fn load(
+        cls: &Bound<'_, PyType>,
+        path: PathBuf,
+        format: Option<&str>,
+    ) -> PyResult<Py<Simulation>> {
+        let py = cls.py();
+        if !cls.is(&py.get_type_bound::<Simulation>()) {
+            return Err(PyTypeError::new_err(format!(
+                "{} cannot be loaded from a snapshot, only Simulation can",
+                cls.qualname()?
+            )));
+        }
+        let snapshot = Snapshot::read(&path, SnapshotFormat::resolve(format, &path)?)?;
+        Py::new(py, Simulation::from_snapshot(py, snapshot)?)
+    }
+
+    #[getter(config)]
+    fn get_config(&self) -> SimulationConfig {
+        self.config.clone()
+    }
+
+    #[getter(environment)]
+    fn get_environment(&self, py: Python<'_>) -> Py<Environment> {
+        self.environment.clone_ref(py)
+    }
+
+    // The first colony, the only one unless `num_colonies` says otherwise.
+    #[getter(colony)]
+    fn get_colony(&self, py: Python<'_>) -> Py<Colony> {
+        self.colonies[0].clone_ref(py)
+    }
+
+    #[getter(colonies)]
+    fn get_colonies(&self, py: Python<'_>) -> Vec<Py<Colony>> {
+        self.colonies
+            .iter()
+            .map(|colony| colony.clone_ref(py))
+            .collect()
+    }
+
+    #[getter(ant_paths)]
+    fn get_ant_paths(&self) -> BTreeMap<u64, Vec<Position>> {
+        self.ant_paths.clone()
+    }
+
+    #[getter(track_paths)]
+    fn get_track_paths(&self) -> bool {
+        self.track_paths
+    }
+
+    #[setter(track_paths)]
+    fn set_track_paths(&mut self, track_paths: bool) {
+        self.track_paths = track_paths;
+    }
+
+    // Starts streaming every ant's state after each step to `path`, closing
+    // any earlier recording first. `format` is "ipc" (Arrow IPC file) or
+    // "parquet"; by default `.parquet` paths get Parquet.
+    #[pyo3(signature = (path, format=None, batch_size=DEFAULT_BATCH_SIZE))]
+    fn record_trajectories(
+        &mut self,
+        path: PathBuf,
+        format: Option<&str>,
+        batch_size: usize,
+    ) -> PyResult<()> {
+        self.stop_recording()?;
+        let format = RecordFormat::resolve(format, &path)?;
+        self.recorder = Some(TrajectoryRecorder::create(&path, format, batch_size)?);
+        Ok(())
+    }
+
+    // Finishes the current recording, if any, and returns its path.
+    fn stop_recording(&mut self) -> PyResult<Option<PathBuf>> {
+        match self.recorder.take() {
+            Some(mut recorder) => {
+                recorder.finish()?;
+                Ok(Some(recorder.path().to_path_buf()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[getter(recording)]
+    fn get_recording(&self) -> Option<PathBuf> {
+        self.recorder
+            .as_ref()
+            .map(|recorder| recorder.path().to_path_buf())
+    }
+
+    #[getter(current_time)]
+    fn get_current_time(&self) -> f64 {
+        self.current_time
+    }
+
+    #[getter(seed)]
+    fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    #[pyo3(signature = (context=None))]
+    fn run<'py>(
+        &mut self,
+        py: Python<'py>,
+        context: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        if let Some(context) = context {
+            if context.is_truthy()? {
+                println!("Using context: {}", context);
+            }
+        }
+
+        while self.current_time < self.config.simulation_duration {
+            self.step(py, self.config.animation.step_interval)?;
+        }
+        self.get_stats(py)
+    }
+
+    // Draws the current state like one frame of `animate` and writes it as a
+    // PNG.
+    #[pyo3(signature = (path, cell_size=DEFAULT_CELL_SIZE))]
+    fn save_frame(&self, py: Python<'_>, path: PathBuf, cell_size: u32) -> PyResult<()> {
+        let environment = self.environment.borrow(py);
+        let frame = self.with_colonies(py, |colonies| {
+            render::render(&environment, colonies, cell_size)
+        });
+        render::write_png(&path, &frame).map_err(|e| render_error(&path, e))
+    }
+
+    // Steps the simulation `frames` times, as `animate` would, and encodes
+    // the frame after every step without matplotlib. `format` is "gif",
+    // "apng" or "frames" (a directory of PNGs); by default it follows the
+    // extension of `path`.
+    #[pyo3(signature = (path, frames=None, format=None, cell_size=DEFAULT_CELL_SIZE, fps=None))]
+    fn save_animation(
+        &mut self,
+        py: Python<'_>,
+        path: PathBuf,
+        frames: Option<u32>,
+        format: Option<&str>,
+        cell_size: u32,
+        fps: Option<u32>,
+    ) -> PyResult<()> {
+        let format = AnimationFormat::resolve(format, &path).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "unknown animation format {:?}, expected 'gif', 'apng' or 'frames'",
+                format.unwrap_or_default()
+            ))
+        })?;
+        let frames = frames.unwrap_or_else(|| render::default_frame_count(&self.config));
+        let (width, height) = (
+            self.config.grid_size.0 as u32 * cell_size,
+            self.config.grid_size.1 as u32 * cell_size,
+        );
+        let fps = fps.unwrap_or(self.config.animation.fps);
+        let mut writer = AnimationWriter::create(&path, format, width, height, fps, frames)
+            .map_err(|e| render_error(&path, e))?;
+        for _ in 0..frames {
+            self.step(py, self.config.animation.step_interval)?;
+            let environment = self.environment.borrow(py);
+            let frame = self.with_colonies(py, |colonies| {
+                render::render(&environment, colonies, cell_size)
+            });
+            writer.push(&frame).map_err(|e| render_error(&path, e))?;
+        }
+        writer.finish().map_err(|e| render_error(&path, e))
+    }
+
+    // Watches the simulation live in the terminal, stepping it until the
+    // user quits or `steps` steps have run; see `viewer::run` for the keys.
+    #[pyo3(signature = (steps=None))]
+    fn view(&mut self, py: Python<'_>, steps: Option<usize>) -> PyResult<()> {
+        let options = ViewOptions::new(&self.config, steps);
+        viewer::run(
+            &mut SimulationView {
+                simulation: self,
+                py,
+            },
+            &options,
+        )
+    }
+
+    #[pyo3(name = "step")]
+    fn py_step(&mut self, py: Python<'_>, time_delta: f64) -> PyResult<()> {
+        self.step(py, time_delta)
+    }
+
+    fn get_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = self
+            .with_colonies(py, |colonies| RunStats::new(colonies, self.current_time))
+            .into_py_dict(py)?;
+        stats.set_item("ant_paths", self.ant_paths.clone())?;
+        Ok(stats)
+    }
+}
+
+#[cfg(feature = "python")]
+struct SimulationView<'a, 'py> {
+    simulation: &'a mut Simulation,
+    py: Python<'py>,
+}
+
+#[cfg(feature = "python")]
+impl ViewSource for SimulationView<'_, '_> {
+    type Error = PyErr;
+
+    fn step(&mut self) -> PyResult<()> {
+        let time_delta = self.simulation.config.animation.step_interval;
+        self.simulation.step(self.py, time_delta)
+    }
+
+    fn view<R>(&self, f: impl FnOnce(&Environment, &[&Colony], f64) -> R) -> R {
+        let environment = self.simulation.environment.borrow(self.py);
+        self.simulation.with_colonies(self.py, |colonies| {
+            f(&environment, colonies, self.simulation.current_time)
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+fn render_error(path: &std::path::Path, error: std::io::Error) -> PyErr {
+    pyo3::exceptions::PyIOError::new_err(format!("{}: {error}", path.display()))
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "python")]
+use pyo3::{
+    exceptions::{PyIOError, PyValueError},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::colony::Colony;
+use crate::config::SimulationConfig;
+use crate::environment::{Environment, Position};
+
+// Bumped whenever a change to the simulation state breaks old snapshots.
+pub const SNAPSHOT_VERSION: u32 = 11;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Binary,
+    Json,
+}
+
+impl SnapshotFormat {
+    // An explicit "binary"/"json" wins; otherwise `.json` files are JSON and
+    // everything else is bincode.
+    pub fn resolve(format: Option<&str>, path: &Path) -> Result<SnapshotFormat, SnapshotError> {
+        match format {
+            Some("binary") => Ok(SnapshotFormat::Binary),
+            Some("json") => Ok(SnapshotFormat::Json),
+            Some(other) => Err(SnapshotError::UnknownFormat(other.to_string())),
+            None if path.extension().is_some_and(|ext| ext == "json") => Ok(SnapshotFormat::Json),
+            None => Ok(SnapshotFormat::Binary),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    UnknownFormat(String),
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Encode {
+        path: PathBuf,
+        message: String,
+    },
+    // Written by a build with another `SNAPSHOT_VERSION`
+    Version {
+        path: PathBuf,
+        version: u32,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::UnknownFormat(format) => write!(
+                f,
+                "unknown snapshot format {format:?}, expected 'binary' or 'json'"
+            ),
+            SnapshotError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            SnapshotError::Encode { path, message } => write!(f, "{}: {message}", path.display()),
+            SnapshotError::Version { path, version } => write!(
+                f,
+                "{}: snapshot version {version} is not supported (expected {SNAPSHOT_VERSION})",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<SnapshotError> for PyErr {
+    fn from(error: SnapshotError) -> PyErr {
+        match error {
+            SnapshotError::Io { .. } => PyIOError::new_err(error.to_string()),
+            _ => PyValueError::new_err(error.to_string()),
+        }
+    }
+}
+
+// Everything needed to resume a `Simulation` bit-exactly, including the RNG
+// streams inside the environment and colonies. Borrowed for saving...
+#[derive(Serialize)]
+pub struct SnapshotRef<'a> {
+    pub config: &'a SimulationConfig,
+    pub environment: &'a Environment,
+    pub colonies: &'a [&'a Colony],
+    pub ant_paths: &'a BTreeMap<u64, Vec<Position>>,
+    pub current_time: f64,
+    pub seed: u64,
+}
+
+// ...and owned when loading. The field order must match `SnapshotRef`.
+#[derive(Deserialize)]
+pub struct Snapshot {
+    pub config: SimulationConfig,
+    pub environment: Environment,
+    pub colonies: Vec<Colony>,
+    pub ant_paths: BTreeMap<u64, Vec<Position>>,
+    pub current_time: f64,
+    pub seed: u64,
+}
+
+// The version comes first in either format, so a snapshot from another
+// version is turned away before its body is decoded: a bare `u32` ahead of
+// the body in bincode, the first key of the object in JSON.
+#[derive(Serialize)]
+struct JsonSnapshotRef<'a, 'b> {
+    version: u32,
+    snapshot: &'b SnapshotRef<'a>,
+}
+
+// The rest of the object is skipped over without being decoded.
+#[derive(Deserialize)]
+struct JsonHeader {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct JsonSnapshot {
+    snapshot: Snapshot,
+}
+
+impl SnapshotRef<'_> {
+    pub fn write(&self, path: &Path, format: SnapshotFormat) -> Result<(), SnapshotError> {
+        let file = File::create(path).map_err(|e| io_error(path, e))?;
+        let mut writer = BufWriter::new(file);
+        match format {
+            SnapshotFormat::Binary => bincode::serialize_into(&mut writer, &SNAPSHOT_VERSION)
+                .and_then(|()| bincode::serialize_into(&mut writer, self))
+                .map_err(|e| encode_error(path, e))?,
+            SnapshotFormat::Json => {
+                let snapshot = JsonSnapshotRef {
+                    version: SNAPSHOT_VERSION,
+                    snapshot: self,
+                };
+                serde_json::to_writer(&mut writer, &snapshot).map_err(|e| encode_error(path, e))?
+            }
+        }
+        writer.flush().map_err(|e| io_error(path, e))
+    }
+}
+
+impl Snapshot {
+    pub fn read(path: &Path, format: SnapshotFormat) -> Result<Snapshot, SnapshotError> {
+        let mut snapshot = Snapshot::decode(path, format)?;
+        // The environment and colonies don't store the config themselves, so
+        // they all get the one copy written at the top
+        snapshot.environment.config = snapshot.config.clone();
+        for colony in &mut snapshot.colonies {
+            colony.config = snapshot.config.clone();
+        }
+        Ok(snapshot)
+    }
+
+    fn decode(path: &Path, format: SnapshotFormat) -> Result<Snapshot, SnapshotError> {
+        match format {
+            SnapshotFormat::Binary => {
+                let file = File::open(path).map_err(|e| io_error(path, e))?;
+                let mut reader = BufReader::new(file);
+                let version =
+                    bincode::deserialize_from(&mut reader).map_err(|e| encode_error(path, e))?;
+                check_version(path, version)?;
+                bincode::deserialize_from(reader).map_err(|e| encode_error(path, e))
+            }
+            SnapshotFormat::Json => {
+                let json = fs::read(path).map_err(|e| io_error(path, e))?;
+                let header: JsonHeader =
+                    serde_json::from_slice(&json).map_err(|e| encode_error(path, e))?;
+                check_version(path, header.version)?;
+                let JsonSnapshot { snapshot } =
+                    serde_json::from_slice(&json).map_err(|e| encode_error(path, e))?;
+                Ok(snapshot)
+            }
+        }
+    }
+}
+
+fn check_version(path: &Path, version: u32) -> Result<(), SnapshotError> {
+    if version == SNAPSHOT_VERSION {
+        Ok(())
+    } else {
+        Err(SnapshotError::Version {
+            path: path.to_path_buf(),
+            version,
+        })
+    }
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> SnapshotError {
+    SnapshotError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+fn encode_error(path: &Path, error: impl fmt::Display) -> SnapshotError {
+    SnapshotError::Encode {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::update_colonies;
+    use crate::config::{EncounterMode, EvaporationMode, RecruitmentMode};
+    use crate::simulation::populate;
+
+    fn step(config: &SimulationConfig, world: &mut (Environment, Vec<Colony>), time: &mut f64) {
+        let (environment, colonies) = world;
+        let time_delta = config.animation.step_interval;
+        *time += time_delta;
+        environment.update(*time);
+        update_colonies(environment, colonies, time_delta);
+        for colony in colonies.iter_mut() {
+            colony.update(time_delta);
+        }
+    }
+
+    // The whole state as bytes, RNG streams included.
+    fn state(world: &(Environment, Vec<Colony>)) -> Vec<u8> {
+        bincode::serialize(world).unwrap()
+    }
+
+    #[test]
+    fn loaded_snapshot_resumes_bit_for_bit() {
+        let mut config = SimulationConfig {
+            grid_size: (30, 20),
+            num_ants: 15,
+            num_colonies: 2,
+            encounter_mode: EncounterMode::Fight,
+            pheromone_evaporation_mode: EvaporationMode::Lazy,
+            ..SimulationConfig::default()
+        };
+        config.castes.demand.soldier = 0.5;
+        config.recruitment.mode = RecruitmentMode::Mass;
+        config.energy.enabled = true;
+
+        for format in [SnapshotFormat::Binary, SnapshotFormat::Json] {
+            let mut world = populate(&config, 11).unwrap();
+            let mut time = 0.0;
+            for _ in 0..100 {
+                step(&config, &mut world, &mut time);
+            }
+
+            let path = std::env::temp_dir().join(format!(
+                "ants_rs_snapshot_{}_{format:?}",
+                std::process::id()
+            ));
+            let colonies: Vec<&Colony> = world.1.iter().collect();
+            SnapshotRef {
+                config: &config,
+                environment: &world.0,
+                colonies: &colonies,
+                ant_paths: &BTreeMap::new(),
+                current_time: time,
+                seed: 11,
+            }
+            .write(&path, format)
+            .unwrap();
+            let snapshot = Snapshot::read(&path, format).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            let mut loaded_time = snapshot.current_time;
+            let mut loaded = (snapshot.environment, snapshot.colonies);
+            assert_eq!(state(&loaded), state(&world), "{format:?}");
+            let config_json = serde_json::to_string(&config).unwrap();
+            assert_eq!(
+                serde_json::to_string(&loaded.0.config).unwrap(),
+                config_json
+            );
+            for colony in &loaded.1 {
+                assert_eq!(serde_json::to_string(&colony.config).unwrap(), config_json);
+            }
+            for _ in 0..100 {
+                step(&config, &mut world, &mut time);
+                step(&snapshot.config, &mut loaded, &mut loaded_time);
+            }
+            assert_eq!(loaded_time.to_bits(), time.to_bits(), "{format:?}");
+            assert_eq!(state(&loaded), state(&world), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn other_versions_are_rejected_before_decoding() {
+        let path =
+            std::env::temp_dir().join(format!("ants_rs_snapshot_version_{}", std::process::id()));
+        for format in [SnapshotFormat::Binary, SnapshotFormat::Json] {
+            let header = match format {
+                SnapshotFormat::Binary => bincode::serialize(&(SNAPSHOT_VERSION - 1)).unwrap(),
+                SnapshotFormat::Json => format!(
+                    r#"{{"version": {}, "snapshot": null}}"#,
+                    SNAPSHOT_VERSION - 1
+                )
+                .into_bytes(),
+            };
+            fs::write(&path, header).unwrap();
+            let error = Snapshot::read(&path, format).err().unwrap();
+            assert!(
+                matches!(error, SnapshotError::Version { version, .. } if version == SNAPSHOT_VERSION - 1),
+                "{format:?}: {error}"
+            );
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+// Impassable cells: walls and rocks that ants walk around and food never
+// lands on, for experiments like the double bridge. Maps are drawn by hand,
+// as a PNG or as ASCII art, and stretched over the grid.
+use std::fs::{self, File};
+use std::path::Path;
+
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{BoundaryMode, Neighborhood, SimulationConfig};
+use crate::config_error::ConfigError;
+use crate::environment::Position;
+
+// Luma and alpha thresholds for PNG maps: dark, opaque pixels are walls.
+const WALL_LUMA: u32 = 128;
+const WALL_ALPHA: u8 = 128;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Terrain {
+    // Indexed [x, y] like the environment grid; true where a cell is a wall
+    pub walls: Array2<bool>,
+}
+
+impl Terrain {
+    // No walls at all, the featureless torus of the Python version.
+    pub fn open(grid_size: (usize, usize)) -> Terrain {
+        Terrain {
+            walls: Array2::from_elem(grid_size, false),
+        }
+    }
+
+    // The map named by `terrain_map`, or open terrain without one.
+    pub fn for_config(config: &SimulationConfig) -> Result<Terrain, ConfigError> {
+        match &config.terrain_map {
+            Some(path) => Terrain::load(path, config.grid_size),
+            None => Ok(Terrain::open(config.grid_size)),
+        }
+    }
+
+    // Reads a PNG (`.png`) or, for any other extension, an ASCII map where
+    // `#` is a wall and anything else is open. Either way the top row of the
+    // map is the top of the plot (`origin="lower"`), and the map is stretched
+    // to `grid_size` with nearest-neighbour sampling, so a 20x20 sketch works
+    // for a 200x200 grid.
+    pub fn load(path: &Path, grid_size: (usize, usize)) -> Result<Terrain, ConfigError> {
+        let parse_error = |message: String| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+        };
+        let io_error = |source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let rows = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => {
+                let file = File::open(path).map_err(io_error)?;
+                read_png(file).map_err(|e| parse_error(e.to_string()))?
+            }
+            _ => parse_ascii(&fs::read_to_string(path).map_err(io_error)?),
+        };
+        if rows.iter().all(Vec::is_empty) {
+            return Err(parse_error("the terrain map is empty".to_string()));
+        }
+        let terrain = Terrain::from_rows(&rows, grid_size);
+        if terrain.open_cells().next().is_none() {
+            return Err(parse_error(
+                "the terrain map leaves no open cells".to_string(),
+            ));
+        }
+        Ok(terrain)
+    }
+
+    // `rows` go from the top of the map down; short rows are open past
+    // their end.
+    fn from_rows(rows: &[Vec<bool>], grid_size: (usize, usize)) -> Terrain {
+        let (grid_width, grid_height) = grid_size;
+        let map_height = rows.len();
+        let map_width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let walls = Array2::from_shape_fn(grid_size, |(x, y)| {
+            let row = &rows[(grid_height - 1 - y) * map_height / grid_height];
+            row.get(x * map_width / grid_width)
+                .copied()
+                .unwrap_or(false)
+        });
+        Terrain { walls }
+    }
+
+    pub fn is_wall(&self, position: Position) -> bool {
+        self.walls[[position.0, position.1]]
+    }
+
+    pub fn is_open(&self, position: Position) -> bool {
+        !self.is_wall(position)
+    }
+
+    // Where a step of (dx, dy) from `position` lands, if that cell is open.
+    // Ants on the 4-neighbour grid still home in with diagonal steps, but not
+    // between two walls that only touch at a corner, where no straight route
+    // leads through.
+    pub fn step(
+        &self,
+        position: Position,
+        (dx, dy): (i64, i64),
+        boundary: BoundaryMode,
+        neighborhood: Neighborhood,
+    ) -> Option<Position> {
+        let grid_size = self.walls.dim();
+        let next = boundary
+            .step(position, dx, dy, grid_size)
+            .filter(|&next| self.is_open(next))?;
+        if neighborhood == Neighborhood::VonNeumann && dx != 0 && dy != 0 {
+            let open = |dx, dy| {
+                boundary
+                    .step(position, dx, dy, grid_size)
+                    .is_some_and(|side| self.is_open(side))
+            };
+            if !open(dx, 0) && !open(0, dy) {
+                return None;
+            }
+        }
+        Some(next)
+    }
+
+    pub fn open_cells(&self) -> impl Iterator<Item = Position> + '_ {
+        self.walls
+            .indexed_iter()
+            .filter(|(_, &wall)| !wall)
+            .map(|(position, _)| position)
+    }
+}
+
+fn parse_ascii(text: &str) -> Vec<Vec<bool>> {
+    text.lines()
+        .map(|line| line.chars().map(|c| c == '#').collect())
+        .collect()
+}
+
+fn read_png(file: File) -> Result<Vec<Vec<bool>>, png::DecodingError> {
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let channels = info.color_type.samples();
+    let rows = buffer[..info.buffer_size()]
+        .chunks_exact(info.line_size)
+        .map(|line| {
+            line.chunks_exact(channels)
+                .map(|pixel| {
+                    let (luma, alpha) = match *pixel {
+                        [gray] => (u32::from(gray), u8::MAX),
+                        [gray, alpha] => (u32::from(gray), alpha),
+                        [r, g, b] => (luma(r, g, b), u8::MAX),
+                        [r, g, b, alpha] => (luma(r, g, b), alpha),
+                        _ => unreachable!("8-bit PNGs have 1 to 4 channels"),
+                    };
+                    luma < WALL_LUMA && alpha >= WALL_ALPHA
+                })
+                .collect()
+        })
+        .collect();
+    Ok(rows)
+}
+
+// Rec. 601 luma, in 0..=255
+fn luma(r: u8, g: u8, b: u8) -> u32 {
+    (299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000
+}
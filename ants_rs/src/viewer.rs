@@ -0,0 +1,316 @@
+// A live view of the simulation in the terminal, for when the matplotlib
+// window of `Simulation.animate` isn't available (over SSH, say). Each
+// character is two grid blocks drawn with an upper half-block in 24-bit
+// color, and grids larger than the terminal are averaged down to fit.
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::colony::update_colonies;
+use crate::colony::Colony;
+use crate::config::SimulationConfig;
+use crate::config_error::ConfigError;
+use crate::environment::Environment;
+use crate::render::{blend, colony_colors, pheromone_rgba, to_byte, GREEN, WALL, WHITE};
+use crate::simulation::{populate, RunStats};
+
+// The title line and the status line
+const RESERVED_ROWS: u16 = 2;
+const MIN_SPEED: f64 = 1.0 / 8.0;
+const MAX_SPEED: f64 = 64.0;
+
+// Something the viewer can advance and look at: the `ants-sim` state or a
+// Python `Simulation`.
+pub trait ViewSource {
+    type Error: From<io::Error>;
+
+    // Advances by one `animation.step_interval`.
+    fn step(&mut self) -> Result<(), Self::Error>;
+
+    // Calls `f` with the current state and time.
+    fn view<R>(&self, f: impl FnOnce(&Environment, &[&Colony], f64) -> R) -> R;
+}
+
+// A simulation owned by the viewer, set up like `run_headless` does.
+pub struct Headless {
+    pub environment: Environment,
+    pub colonies: Vec<Colony>,
+    pub current_time: f64,
+    time_delta: f64,
+}
+
+impl Headless {
+    pub fn new(config: &SimulationConfig, seed: u64) -> Result<Headless, ConfigError> {
+        let (environment, colonies) = populate(config, seed)?;
+        Ok(Headless {
+            environment,
+            colonies,
+            current_time: 0.0,
+            time_delta: config.animation.step_interval,
+        })
+    }
+
+    pub fn stats(&self) -> RunStats {
+        RunStats::new(&self.colonies, self.current_time)
+    }
+}
+
+impl ViewSource for Headless {
+    type Error = io::Error;
+
+    fn step(&mut self) -> io::Result<()> {
+        self.current_time += self.time_delta;
+        self.environment.update(self.current_time);
+        update_colonies(&mut self.environment, &mut self.colonies, self.time_delta);
+        for colony in &mut self.colonies {
+            colony.update(self.time_delta);
+        }
+        Ok(())
+    }
+
+    fn view<R>(&self, f: impl FnOnce(&Environment, &[&Colony], f64) -> R) -> R {
+        let colonies: Vec<&Colony> = self.colonies.iter().collect();
+        f(&self.environment, &colonies, self.current_time)
+    }
+}
+
+// `Simulation.animate` draws a frame every `step_interval` seconds; so does
+// the viewer at speed 1. `max_steps` stops the simulation, not the viewer.
+pub struct ViewOptions {
+    pub frame_interval: Duration,
+    pub max_steps: Option<usize>,
+}
+
+impl ViewOptions {
+    pub fn new(config: &SimulationConfig, max_steps: Option<usize>) -> ViewOptions {
+        ViewOptions {
+            frame_interval: Duration::from_secs_f64(config.animation.step_interval),
+            max_steps,
+        }
+    }
+}
+
+struct Controls {
+    paused: bool,
+    // Steps per frame; below 1 the simulation advances every few frames
+    speed: f64,
+    pending: f64,
+    steps: usize,
+}
+
+enum Action {
+    Quit,
+    TogglePause,
+    Step,
+    Faster,
+    Slower,
+}
+
+// Takes over the terminal until the user quits: space pauses, `n` or the
+// right arrow steps once while paused, `+`/`-` double or halve the speed
+// and `q`, Esc or Ctrl+C quit. The terminal is restored on every exit path.
+pub fn run<S: ViewSource>(source: &mut S, options: &ViewOptions) -> Result<(), S::Error> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    if let Err(error) = execute!(stdout, EnterAlternateScreen, Hide) {
+        let _ = terminal::disable_raw_mode();
+        return Err(error.into());
+    }
+    let result = event_loop(source, options, &mut stdout);
+    let restored =
+        execute!(stdout, Show, LeaveAlternateScreen).and_then(|()| terminal::disable_raw_mode());
+    result?;
+    Ok(restored?)
+}
+
+fn event_loop<S: ViewSource>(
+    source: &mut S,
+    options: &ViewOptions,
+    stdout: &mut io::Stdout,
+) -> Result<(), S::Error> {
+    let mut controls = Controls {
+        paused: false,
+        speed: 1.0,
+        pending: 0.0,
+        steps: 0,
+    };
+    let finished = |controls: &Controls| options.max_steps.is_some_and(|max| controls.steps >= max);
+    loop {
+        redraw(source, &controls, finished(&controls), stdout)?;
+
+        // Handle keys until the next frame is due
+        let deadline = Instant::now() + options.frame_interval;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            if !event::poll(timeout)? {
+                break;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => match action(key) {
+                    Some(Action::Quit) => return Ok(()),
+                    Some(Action::TogglePause) => controls.paused = !controls.paused,
+                    Some(Action::Step) if controls.paused && !finished(&controls) => {
+                        source.step()?;
+                        controls.steps += 1;
+                    }
+                    Some(Action::Faster) => controls.speed = (controls.speed * 2.0).min(MAX_SPEED),
+                    Some(Action::Slower) => controls.speed = (controls.speed / 2.0).max(MIN_SPEED),
+                    _ => continue,
+                },
+                Event::Resize(..) => queue!(stdout, Clear(ClearType::All))?,
+                _ => continue,
+            }
+            redraw(source, &controls, finished(&controls), stdout)?;
+        }
+
+        if !controls.paused {
+            controls.pending += controls.speed;
+            while controls.pending >= 1.0 && !finished(&controls) {
+                source.step()?;
+                controls.steps += 1;
+                controls.pending -= 1.0;
+            }
+            controls.pending = controls.pending.min(1.0);
+        }
+    }
+}
+
+fn action(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        KeyCode::Char(' ') | KeyCode::Char('p') => Some(Action::TogglePause),
+        KeyCode::Char('n') | KeyCode::Char('.') | KeyCode::Right => Some(Action::Step),
+        KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Up => Some(Action::Faster),
+        KeyCode::Char('-') | KeyCode::Char('_') | KeyCode::Down => Some(Action::Slower),
+        _ => None,
+    }
+}
+
+fn redraw<S: ViewSource>(
+    source: &S,
+    controls: &Controls,
+    finished: bool,
+    stdout: &mut io::Stdout,
+) -> io::Result<()> {
+    let (cols, rows) = terminal::size()?;
+    let (mut lines, scale) = source.view(|environment, colonies, current_time| {
+        draw(environment, colonies, current_time, cols, rows)
+    });
+    let state = if finished {
+        "finished"
+    } else if controls.paused {
+        "paused"
+    } else {
+        "running"
+    };
+    let mut status = format!("[{state}] speed x{}", controls.speed);
+    if scale > 1 {
+        let _ = write!(status, " | 1:{scale}");
+    }
+    status.push_str(" | space pause, n step, +/- speed, q quit");
+    lines.push(truncate(&status, cols));
+
+    for (row, line) in lines.iter().enumerate() {
+        queue!(stdout, MoveTo(0, row as u16))?;
+        write!(stdout, "{line}\x1b[0m\x1b[K")?;
+    }
+    queue!(stdout, Clear(ClearType::FromCursorDown))?;
+    stdout.flush()
+}
+
+// The title line and the grid for a `cols` x `rows` terminal, leaving a row
+// for the status line, plus how many cells wide and tall each block is.
+// Like the plot (`origin="lower"`), y grows upwards.
+pub fn draw(
+    environment: &Environment,
+    colonies: &[&Colony],
+    current_time: f64,
+    cols: u16,
+    rows: u16,
+) -> (Vec<String>, usize) {
+    let title = format!(
+        "Time: {current_time:.1}s | Ants: {} | Eggs Waiting: {}",
+        colonies
+            .iter()
+            .map(|colony| colony.ants.len())
+            .sum::<usize>(),
+        colonies.iter().map(|colony| colony.eggs).sum::<usize>()
+    );
+    let mut lines = vec![truncate(&title, cols)];
+
+    let (grid_width, grid_height) = environment.config.grid_size;
+    let cols = usize::from(cols.max(1));
+    let pixel_rows = 2 * usize::from(rows.saturating_sub(RESERVED_ROWS).max(1));
+    let scale = 1
+        .max(grid_width.div_ceil(cols))
+        .max(grid_height.div_ceil(pixel_rows));
+    let width = grid_width.div_ceil(scale);
+    let height = grid_height.div_ceil(scale);
+
+    // Pheromones and walls, averaged over each block
+    let mut pixels = vec![[0.0f32; 3]; width * height];
+    let mut counts = vec![0u32; width * height];
+    for x in 0..grid_width {
+        for y in 0..grid_height {
+            let index = (y / scale) * width + x / scale;
+            let cell = if environment.terrain.is_wall((x, y)) {
+                WALL
+            } else {
+                let (color, alpha) = pheromone_rgba(environment, (x, y));
+                blend(WHITE, color, alpha)
+            };
+            for (channel, value) in pixels[index].iter_mut().zip(cell) {
+                *channel += value;
+            }
+            counts[index] += 1;
+        }
+    }
+    for (pixel, &count) in pixels.iter_mut().zip(&counts) {
+        *pixel = pixel.map(|channel| channel / count as f32);
+    }
+
+    // Markers, in the order `render` draws them
+    let index = |(x, y): (usize, usize)| (y / scale) * width + x / scale;
+    for colony in colonies {
+        let (ant_color, _) = colony_colors(colony.id);
+        for &position in &colony.ants.positions {
+            pixels[index(position)] = ant_color;
+        }
+    }
+    for colony in colonies {
+        let (_, queen_color) = colony_colors(colony.id);
+        pixels[index(colony.queen.position)] = queen_color;
+    }
+    for position in environment.food_positions.iter() {
+        let pixel = &mut pixels[index(position)];
+        *pixel = blend(*pixel, GREEN, 0.5);
+    }
+
+    // Two block rows per line: the upper one in the foreground of "▀", the
+    // lower one in the background
+    for top in (0..height).rev().step_by(2) {
+        let mut line = String::new();
+        for x in 0..width {
+            let [r, g, b] = pixels[top * width + x].map(to_byte);
+            let _ = write!(line, "\x1b[38;2;{r};{g};{b}m");
+            if top > 0 {
+                let [r, g, b] = pixels[(top - 1) * width + x].map(to_byte);
+                let _ = write!(line, "\x1b[48;2;{r};{g};{b}m");
+            } else {
+                line.push_str("\x1b[49m");
+            }
+            line.push('▀');
+        }
+        lines.push(line);
+    }
+    (lines, scale)
+}
+
+fn truncate(text: &str, cols: u16) -> String {
+    text.chars().take(usize::from(cols)).collect()
+}
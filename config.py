@@ -1,5 +1,7 @@
 # config.py
 
+from typing import Literal
+
 from pydantic import Field, PositiveFloat, PositiveInt, computed_field
 from pydantic_settings import BaseSettings, SettingsConfigDict
 
@@ -24,8 +26,74 @@ class AntConfig(BaseSettings):
     lifespan_extension_on_contribution: PositiveFloat = 20.0
 
 
+# Settings below, up to `SimulationConfig`, are only read by the Rust engine
+# (`ants_rs`); the Python simulation ignores them.
+
+
+# Used when encounter_mode is "fight"
+class CombatConfig(BaseSettings):
+    # Per colony, by index; missing colonies have strength 1
+    strength: list[PositiveFloat] = Field(default_factory=list)
+    aggression: float = Field(default=0.5, ge=0.0, le=1.0)
+    loser_death_chance: float = Field(default=1.0, ge=0.0, le=1.0)
+    winner_death_chance: float = Field(default=0.0, ge=0.0, le=1.0)
+
+
+# Unset fields fall back to `ant` and the top-level settings
+class CasteConfig(BaseSettings):
+    carrying_capacity: PositiveFloat | None = None
+    initial_lifespan: PositiveFloat | None = None
+    speed: PositiveInt = 1  # moves per step
+    perception_radius: int | None = Field(default=None, ge=0)
+    randomness_factor: float | None = Field(default=None, ge=0.0, le=1.0)
+    strength: PositiveFloat = 1.0  # multiplies combat.strength
+
+
+# Each hatchling joins the caste furthest below its target head count
+class CasteDemand(BaseSettings):
+    forager: float = Field(default=1.0, ge=0.0)
+    scout: float = Field(default=0.0, ge=0.0)
+    soldier: float = Field(default=0.0, ge=0.0)
+    nurse: float = Field(default=0.0, ge=0.0)
+    nurses_per_egg: float = Field(default=0.0, ge=0.0)
+    soldiers_per_casualty: float = Field(default=0.0, ge=0.0)
+
+
+class CastesConfig(BaseSettings):
+    forager: CasteConfig = Field(default_factory=CasteConfig)
+    scout: CasteConfig = Field(default_factory=CasteConfig)
+    soldier: CasteConfig = Field(default_factory=CasteConfig)
+    nurse: CasteConfig = Field(default_factory=CasteConfig)
+    demand: CasteDemand = Field(default_factory=CasteDemand)
+    brood_care: float = Field(default=0.5, ge=0.0)
+
+
+# How foragers back from a source with food left call idle nestmates to it
+class RecruitmentConfig(BaseSettings):
+    mode: Literal["none", "tandem", "mass"] = "none"
+    # Recruits per unit of food left, rounded up; the chance of a tandem run
+    recruits_per_food: float = Field(default=0.2, ge=0.0)
+    max_recruits: int = Field(default=10, ge=0)
+    nest_radius: int = Field(default=3, ge=0)
+
+
+# An energy budget per ant, spent moving and refilled by eating carried or
+# stored food, with starvation at zero
+class EnergyConfig(BaseSettings):
+    enabled: bool = False
+    capacity: PositiveFloat = 100.0
+    cost_per_move: float = Field(default=1.0, ge=0.0)
+    metabolic_rate: float = Field(default=0.0, ge=0.0)  # per second
+    hunger_threshold: float = Field(default=0.5, ge=0.0, le=1.0)
+    food_energy: PositiveFloat = 10.0  # per unit of food eaten
+    # Food the colony store loses per ant per second
+    colony_consumption_rate: float = Field(default=0.0, ge=0.0)
+
+
 class SimulationConfig(BaseSettings):
-    model_config = SettingsConfigDict(env_prefix="ANTS_", extra="ignore")
+    model_config = SettingsConfigDict(
+        env_prefix="ANTS_", env_nested_delimiter="__", extra="ignore"
+    )
 
     grid_size: tuple[PositiveInt, PositiveInt] = (200, 200)
     num_ants: PositiveInt = 100
@@ -44,6 +112,28 @@ class SimulationConfig(BaseSettings):
     randomness_factor: PositiveFloat = 0.3
     enable_multiple_pheromones: bool = True
 
+    # Rust engine only (`ants_rs`); the Python simulation ignores everything
+    # from here down to `energy`
+
+    # "lazy" decays each cell when it is read
+    pheromone_evaporation_mode: Literal["eager", "lazy"] = "eager"
+    # A PNG or ASCII map of walls
+    terrain_map: str | None = None
+    # "field" routes returning ants around walls
+    homing_mode: Literal["auto", "greedy", "field"] = "auto"
+    # What happens at the edges of the grid
+    boundary_mode: Literal["torus", "bounded", "reflecting"] = "torus"
+    # 4, 8 or 6 (hexagonal) neighbours per cell
+    neighborhood: Literal["von_neumann", "moore", "hex"] = "von_neumann"
+    # Colonies competing on the same grid
+    num_colonies: PositiveInt = Field(default=1, le=256)
+    food_sharing: Literal["shared", "exclusive"] = "shared"
+    encounter_mode: Literal["ignore", "avoid", "steal", "fight"] = "ignore"
+    combat: CombatConfig = Field(default_factory=CombatConfig)
+    castes: CastesConfig = Field(default_factory=CastesConfig)
+    recruitment: RecruitmentConfig = Field(default_factory=RecruitmentConfig)
+    energy: EnergyConfig = Field(default_factory=EnergyConfig)
+
     animation: AnimationConfig = Field(default_factory=AnimationConfig)
 
     @computed_field
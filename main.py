@@ -6,12 +6,22 @@ from prefect.logging import get_run_logger
 from config import SimulationConfig
 from simulation import Simulation
 
+try:
+    from simulation import NativeSimulation
+except ImportError:  # the Rust extension is optional
+    NativeSimulation = None
+
 
 @flow(log_prints=True)
 def animate_simulation(
-    config: SimulationConfig | None = None, save_animation: bool = False
+    config: SimulationConfig | None = None,
+    save_animation: bool = False,
+    native: bool = False,
 ) -> dict[str, Any]:
-    simulation = Simulation.from_config_or_default(config)
+    if native and NativeSimulation is None:
+        raise RuntimeError("native=True requires the ants_rs extension (make setup)")
+    simulation_type = NativeSimulation if native else Simulation
+    simulation = simulation_type.from_config_or_default(config)
     try:
         animation = task(simulation.animate)()
         if save_animation:
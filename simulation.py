@@ -6,6 +6,11 @@ import matplotlib.pyplot as plt
 import numpy as np
 from pydantic import BaseModel, ConfigDict, Field
 
+try:
+    import ants_rs
+except ImportError:  # the Rust extension is optional
+    ants_rs = None
+
 from config import SimulationConfig
 from environment import Environment
 from models import Ant, Colony, Position, Queen
@@ -317,3 +322,15 @@ class Simulation(BaseModel):
             + elements["ant_annotations"]
             + elements["food_annotations"]
         )
+
+
+if ants_rs is not None:
+
+    class NativeSimulation(ants_rs.Simulation):
+        """The Rust engine from `ants_rs`, drawn with the same matplotlib code."""
+
+        animate = Simulation.animate
+        _initialize_plot_elements = Simulation._initialize_plot_elements
+        _update_plot_elements = Simulation._update_plot_elements
+        get_combined_pheromone_grid = Simulation.get_combined_pheromone_grid
+        _get_artists = Simulation._get_artists